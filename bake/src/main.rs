@@ -0,0 +1,248 @@
+//! Bakes the irradiance volumes of chambers offline, without a window.
+//!
+//! Usage: `fere-bake <scene.yml>`
+//!
+//! It renders the emissive objects of the scene sweep by sweep, where a sweep updates every probe
+//! of a chamber (exactly once, with the `Linear` priority), until the SH coefficients converge.
+//! That is, the largest change of the coefficients of a chamber over a sweep falls below `threshold`,
+//! or `sweeps` is reached. Then it writes the probe volume of the chamber to its `output`,
+//! which can be loaded with `ChamberConfig::probe_volume`, and stops baking it.
+//! The paths in the scene are relative to the scene file.
+
+use fere::prelude::*;
+use rops::*;
+use serde::Deserialize;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use surface::{no_normal_map, EmissiveStaticI, GeneralI, TexVar, TimepointI};
+
+#[derive(Debug, Deserialize)]
+struct BakeScene {
+    fere_configs: FereConfigs,
+    /// Stops when the largest change of the SH coefficients over a sweep falls below this.
+    threshold: f32,
+    /// The maximum number of sweeps, even before converging.
+    sweeps: usize,
+    chambers: Vec<BakeChamber>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BakeChamber {
+    bpos: Vec3,
+    size: Vec3,
+    output: String,
+    objects: Vec<BakeObject>,
+}
+
+/// An emissive object which lights the probes.
+#[derive(Debug, Deserialize)]
+struct BakeObject {
+    /// An `.obj` file.
+    mesh: String,
+    /// The center of the object.
+    pos: Vec3,
+    size: Vec3,
+    /// The rotation around the z axis, in degree.
+    rotation: f32,
+    basecolor: IVec3,
+    emission: IVec3,
+    emission_intensity: u8,
+}
+
+fn read_mesh(path: &Path) -> Result<Arc<Mesh>, String> {
+    let name = path.to_string_lossy();
+    let file = File::open(path).map_err(|e| format!("{}: {}", name, e))?;
+    let mesh_data = fere_resources::mesh::obj::import_single(&name, BufReader::new(file))?;
+    let mut mesh = Mesh::new(None, mesh_data);
+    mesh.buffer();
+    Ok(Arc::new(mesh))
+}
+
+/// A chamber being baked.
+struct Baking {
+    chamber_index: ChamberIndex,
+    objects: Vec<(Arc<Mesh>, Mat4, EmissiveStaticI)>,
+    output: PathBuf,
+    probes: u64,
+    /// The number of sweeps done so far.
+    sweep: usize,
+    /// The SH coefficients at the end of the last sweep.
+    previous: Vec<f32>,
+}
+
+fn bake(scene_path: &Path) -> Result<(), String> {
+    let base = scene_path.parent().unwrap_or_else(|| Path::new("."));
+    let scene: BakeScene = serde_yaml::from_str(
+        &std::fs::read_to_string(scene_path)
+            .map_err(|e| format!("{}: {}", scene_path.display(), e))?,
+    )
+    .map_err(|e| format!("{}: {}", scene_path.display(), e))?;
+    if scene.fere_configs.irradiance_volume.is_none() {
+        return Err("`irradiance_volume` must be enabled in `fere_configs`".to_owned());
+    }
+    if scene.sweeps == 0 {
+        return Err("`sweeps` must be positive".to_owned());
+    }
+
+    let _context = fere_window::create_headless_context(scene.fere_configs.resolution);
+    let mut fere = Fere::new(scene.fere_configs.clone());
+
+    let mut chambers = Vec::new();
+    for chamber in &scene.chambers {
+        let chamber_index = fere
+            .add_chamber(ChamberConfig {
+                bpos: chamber.bpos,
+                size: chamber.size,
+                probe_volume: None,
+                irradiance_volume: None,
+            })
+            .map_err(|e| e.to_string())?;
+        let number = fere
+            .probe_number(chamber_index)
+            .map_err(|e| e.to_string())?;
+
+        let mut objects = Vec::new();
+        for object in &chamber.objects {
+            let mesh = read_mesh(&base.join(&object.mesh))?;
+            let x_angle = object.rotation.to_radians();
+            let y_angle = (object.rotation + 90.0).to_radians();
+            let ori = Ori::new(
+                object.pos,
+                object.size,
+                Vec3::new(x_angle.cos(), x_angle.sin(), 0.0),
+                Vec3::new(y_angle.cos(), y_angle.sin(), 0.0),
+            );
+            let surface = EmissiveStaticI::with_uniform_timepoints(
+                GeneralI {
+                    basecolor: TexVar::U(object.basecolor),
+                    roughness: TexVar::U(128),
+                    metalness: TexVar::U(0),
+                    normal: no_normal_map(),
+                },
+                TimepointI {
+                    emission: TexVar::U(object.emission),
+                    emission_intensity: TexVar::U(object.emission_intensity),
+                    smoothness: 1.0,
+                },
+            );
+            objects.push((mesh, *ori.trans(), surface));
+        }
+        chambers.push(Baking {
+            chamber_index,
+            objects,
+            output: base.join(&chamber.output),
+            probes: (number.x * number.y * number.z) as u64,
+            sweep: 0,
+            previous: fere
+                .probe_coefficients(chamber_index)
+                .map_err(|e| e.to_string())?,
+        });
+    }
+
+    // Each frame renders `faces_per_frame` faces of probes for every chamber.
+    while !chambers.is_empty() {
+        let mut camera = SetCamera::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            60.0_f32.to_radians(),
+            1.0,
+            0.1,
+            1000.0,
+        );
+        camera.trans();
+        let (mut frame, renderer) = fere.new_frame(FrameConfig {
+            camera,
+            show_lightvolume_outline: false,
+            tone_mapping: Default::default(),
+            bloom: None,
+            clock: Default::default(),
+        });
+        for chamber in &chambers {
+            for (mesh, trans, surface) in &chamber.objects {
+                frame.push(DrawEmissiveStatic {
+                    object: Object {
+                        mesh: Arc::clone(mesh),
+                        shadow: false,
+                        irradiance_volume: true,
+                        trans: *trans,
+                        chamber_index: chamber.chamber_index,
+                        id: 1,
+                    },
+                    surface: surface.clone(),
+                    point_light: None,
+                });
+            }
+        }
+        frame.end();
+        fere.end_frame(renderer.render());
+
+        let mut finished = Vec::new();
+        for (i, chamber) in chambers.iter_mut().enumerate() {
+            let count = fere
+                .probe_update_count(chamber.chamber_index)
+                .map_err(|e| e.to_string())?;
+            if count < chamber.probes * (chamber.sweep + 1) as u64 {
+                continue;
+            }
+            chamber.sweep = ((count / chamber.probes) as usize).min(scene.sweeps);
+            let current = fere
+                .probe_coefficients(chamber.chamber_index)
+                .map_err(|e| e.to_string())?;
+            let delta = chamber
+                .previous
+                .iter()
+                .zip(current.iter())
+                .map(|(a, b)| (a - b).abs())
+                .fold(0.0, f32::max);
+            chamber.previous = current;
+            println!(
+                "{}: sweep {}, the largest change of SH coefficients is {}",
+                chamber.output.display(),
+                chamber.sweep,
+                delta
+            );
+
+            if delta < scene.threshold {
+                println!(
+                    "{}: converged after {} sweeps, with the final delta {}",
+                    chamber.output.display(),
+                    chamber.sweep,
+                    delta
+                );
+            } else if chamber.sweep >= scene.sweeps {
+                println!(
+                    "{}: stopped after {} sweeps without converging, with the final delta {}",
+                    chamber.output.display(),
+                    chamber.sweep,
+                    delta
+                );
+            } else {
+                continue;
+            }
+            fere.save_probe_volume(chamber.chamber_index, &chamber.output.to_string_lossy())
+                .map_err(|e| e.to_string())?;
+            println!("Wrote {}", chamber.output.display());
+            finished.push(i);
+        }
+        // So that the finished ones aren't updated any more
+        for i in finished.into_iter().rev() {
+            fere.remove_chamber(chambers.remove(i).chamber_index);
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() != 2 {
+        eprintln!("Usage: fere-bake <scene.yml>");
+        std::process::exit(1);
+    }
+    if let Err(e) = bake(&PathBuf::from(&args[1])) {
+        eprintln!("Failed to bake: {}", e);
+        std::process::exit(1);
+    }
+}
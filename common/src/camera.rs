@@ -46,6 +46,20 @@ impl SetCamera {
         self.view.as_ref().unwrap()
     }
 
+    /// Shifts the projection by `offset` in the normalized device coordinates.
+    ///
+    /// Call this after `trans()`.
+    pub fn jitter(&mut self, offset: Vec2) {
+        let proj = self
+            .projection
+            .as_mut()
+            .expect("Call `trans()` before `jitter()`");
+        for i in 0..4 {
+            proj[(0, i)] += offset.x * proj[(3, i)];
+            proj[(1, i)] += offset.y * proj[(3, i)];
+        }
+    }
+
     pub fn trans(&mut self) {
         let mut up_temp = self.up.normalize();
         let look_vec = (self.look - self.pos).normalize();
@@ -129,6 +129,9 @@ impl Program for Scene {
         let (frame, renderer) = self.renderer.new_frame(FrameConfig {
             camera,
             show_lightvolume_outline: false,
+            tone_mapping: Default::default(),
+            bloom: None,
+            clock: Default::default(),
         });
 
         let resources = Arc::clone(&self.resources);
@@ -16,6 +16,8 @@ impl Program for Scene {
             .add_chamber(ChamberConfig {
                 bpos: Vec3::zeros(),
                 size: Vec3::new(50.0, 50.0, 50.0),
+                probe_volume: None,
+                irradiance_volume: None,
             })
             .unwrap();
         Scene { renderer }
@@ -38,6 +40,9 @@ impl Program for Scene {
         let (frame, renderer) = self.renderer.new_frame(FrameConfig {
             camera,
             show_lightvolume_outline: false,
+            tone_mapping: Default::default(),
+            bloom: None,
+            clock: Default::default(),
         });
         let render_thread = std::thread::spawn(|| render(frame));
         self.renderer.end_frame(renderer.render());
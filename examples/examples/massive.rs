@@ -55,16 +55,23 @@ impl ProgramWithImgui for Scene {
             shadow_resolution: 512,
             probe_resolution: 256,
             max_major_lights: 16,
+            sun_cascade_num: 3,
+            sun_shadow_resolution: 1024,
             video_record: true,
             irradiance_volume: None,
+            ssao: None,
+            ssr: None,
             max_chamber_num: 1,
-            pv_scale: 100.0,
+            point_light_shading: PointLightShading::Tiled,
+            anti_aliasing: AntiAliasing::None,
         };
         let mut renderer = Fere::new(fere_config);
         renderer
             .add_chamber(ChamberConfig {
                 bpos: Vec3::zeros(),
                 size: Vec3::new(500.0, 500.0, 500.0),
+                probe_volume: None,
+                irradiance_volume: None,
             })
             .unwrap();
 
@@ -97,6 +104,9 @@ impl ProgramWithImgui for Scene {
         let (frame, renderer) = self.renderer.new_frame(FrameConfig {
             camera,
             show_lightvolume_outline: false,
+            tone_mapping: Default::default(),
+            bloom: None,
+            clock: Default::default(),
         });
 
         self.state.write().update();
@@ -158,6 +168,7 @@ fn render(mut frame: Frame, state: &SceneState, resources: &Resources) {
                     irradiance_volume: false,
                     trans: *ori.trans(),
                     chamber_index: 0,
+                    id: 0,
                 },
                 surface,
             });
@@ -4,7 +4,7 @@ use fere_util::ui::input_manager::InputManager;
 use fere_window::*;
 use rops::*;
 use std::sync::Arc;
-use surface::{no_normal_map, GeneralI, TexVar};
+use surface::{no_normal_map, GeneralI, TexVar, TransparentI};
 
 #[derive(Debug, serde::Deserialize)]
 struct SceneParams {
@@ -57,6 +57,8 @@ impl ProgramWithImgui for Scene {
             .add_chamber(ChamberConfig {
                 bpos: Vec3::zeros(),
                 size: params.room_size,
+                probe_volume: None,
+                irradiance_volume: None,
             })
             .unwrap();
 
@@ -81,7 +83,8 @@ impl ProgramWithImgui for Scene {
     fn update(&mut self, imgui_ctx: ImgUiContext) -> String {
         let screen_size = self.renderer.configs().resolution;
 
-        self.input_manager.update(imgui_ctx.as_ref());
+        self.input_manager
+            .update(imgui_ctx.as_ref(), &self.renderer);
         self.camera_control
             .update(&self.input_manager.get_input_image());
         let mut camera = SetCamera::new(
@@ -98,6 +101,24 @@ impl ProgramWithImgui for Scene {
         let (frame, renderer) = self.renderer.new_frame(FrameConfig {
             camera,
             show_lightvolume_outline: true,
+            tone_mapping: ToneMapping {
+                operator: ToneMapOperator::AcesFilmic,
+                exposure: Exposure::Auto {
+                    key: 0.18,
+                    adaptation_rate: 0.05,
+                    min: 0.1,
+                    max: 10.0,
+                },
+            },
+            bloom: Some(Bloom {
+                threshold: 1.0,
+                levels: 5,
+                intensity: 0.5,
+            }),
+            clock: FrameClock {
+                frame: self.timer,
+                cycle: 600,
+            },
         });
 
         let timer = self.timer;
@@ -151,6 +172,7 @@ fn render(mut frame: Frame, timer: u64, params: &SceneParams, resources: &Resour
             irradiance_volume: false,
             trans: *ori.trans(),
             chamber_index: 0,
+            id: 1,
         },
         surface,
     });
@@ -176,6 +198,7 @@ fn render(mut frame: Frame, timer: u64, params: &SceneParams, resources: &Resour
             irradiance_volume: false,
             trans: *ori.trans(),
             chamber_index: 0,
+            id: 2,
         },
         surface,
     });
@@ -200,6 +223,7 @@ fn render(mut frame: Frame, timer: u64, params: &SceneParams, resources: &Resour
             irradiance_volume: false,
             trans: *ori.trans(),
             chamber_index: 0,
+            id: 3,
         },
         surface,
     });
@@ -224,6 +248,35 @@ fn render(mut frame: Frame, timer: u64, params: &SceneParams, resources: &Resour
             irradiance_volume: false,
             trans: *ori.trans(),
             chamber_index: 0,
+            id: 4,
+        },
+        surface,
+    });
+
+    // Draw a glass cube
+    let mesh = Arc::clone(&resources.cube);
+    let ori = fere_examples::calc_ori_for_cuboid(
+        params.ceramic_cuboid_bpos,
+        params.ceramic_cuboid_size,
+        params.ceramic_cuboid_rotate,
+    );
+    let surface = TransparentI {
+        general: GeneralI {
+            basecolor: TexVar::U(IVec3::new(150, 200, 255)),
+            roughness: TexVar::U(10),
+            metalness: TexVar::U(0),
+            normal: no_normal_map(),
+        },
+        alpha: TexVar::U(180),
+    };
+    frame.push(DrawTransparent {
+        object: Object {
+            mesh,
+            shadow: false,
+            irradiance_volume: false,
+            trans: *ori.trans(),
+            chamber_index: 0,
+            id: 5,
         },
         surface,
     });
@@ -252,6 +305,11 @@ fn render(mut frame: Frame, timer: u64, params: &SceneParams, resources: &Resour
         xdir,
         ydir,
         perspective: (40.0_f32).to_radians(),
+        shadow: ShadowParams {
+            filter: ShadowFilter::Pcss { light_size: 0.02 },
+            ..Default::default()
+        },
+        attenuation: Default::default(),
         chamber_index: 0,
     });
 
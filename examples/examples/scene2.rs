@@ -76,6 +76,8 @@ impl ProgramWithImgui for Scene {
             .add_chamber(ChamberConfig {
                 bpos: Vec3::zeros(),
                 size: params.room_size,
+                probe_volume: None,
+                irradiance_volume: None,
             })
             .unwrap();
 
@@ -101,7 +103,8 @@ impl ProgramWithImgui for Scene {
     fn update(&mut self, imgui_ctx: ImgUiContext) -> String {
         let screen_size = self.renderer.configs().resolution;
 
-        self.input_manager.update(imgui_ctx.as_ref());
+        self.input_manager
+            .update(imgui_ctx.as_ref(), &self.renderer);
         self.camera_control
             .update(&self.input_manager.get_input_image());
         let mut camera = SetCamera::new(
@@ -118,6 +121,16 @@ impl ProgramWithImgui for Scene {
         let (frame, renderer) = self.renderer.new_frame(FrameConfig {
             camera,
             show_lightvolume_outline: false,
+            tone_mapping: Default::default(),
+            bloom: Some(Bloom {
+                threshold: 1.0,
+                levels: 5,
+                intensity: 0.5,
+            }),
+            clock: FrameClock {
+                frame: self.timer,
+                cycle: 600,
+            },
         });
 
         let timer = self.timer;
@@ -184,6 +197,7 @@ fn render(
             irradiance_volume: false,
             trans: *ori.trans(),
             chamber_index: 0,
+            id: 1,
         },
         surface,
     });
@@ -208,13 +222,14 @@ fn render(
             irradiance_volume: false,
             trans: *ori.trans(),
             chamber_index: 0,
+            id: 2,
         },
         surface,
     });
 
     // Draw spheres
     let mesh = Arc::clone(&resources.sphere);
-    for (pos, color, intensity, radius) in state.spheres.iter() {
+    for (i, (pos, color, intensity, radius)) in state.spheres.iter().enumerate() {
         let ori = Ori::new(
             *pos,
             Vec3::from_element(*radius),
@@ -241,6 +256,7 @@ fn render(
                 irradiance_volume: false,
                 trans: *ori.trans(),
                 chamber_index: 0,
+                id: 3 + i as u32,
             },
             surface,
             point_light: Some(1.0),
@@ -0,0 +1,24 @@
+#version 330 core
+
+// log-luminance with mipmaps
+uniform sampler2D u_tex0;
+// adapted luminance of the previous frame
+uniform sampler2D u_tex1;
+uniform float u_adaptation_rate;
+
+layout (location = 0) out float io_luminance;
+
+void main()
+{
+	int top = int(log2(float(textureSize(u_tex0, 0).x)));
+	float average = exp(texelFetch(u_tex0, ivec2(0, 0), top).r);
+	float prev = texelFetch(u_tex1, ivec2(0, 0), 0).r;
+
+	// the first frame
+	if (prev <= 0.0)
+	{
+		io_luminance = average;
+		return;
+	}
+	io_luminance = mix(prev, average, clamp(u_adaptation_rate, 0.0, 1.0));
+}
@@ -0,0 +1,29 @@
+#version 330 core
+
+// the larger level, with bilinear filtering
+uniform sampler2D u_tex0;
+// negative to take every pixel
+uniform float u_bloom_threshold;
+uniform vec2 u_target_size;
+
+layout (location = 0) out vec3 io_color;
+
+vec3 bright(vec3 color)
+{
+	if (u_bloom_threshold < 0.0) return color;
+	float lum = dot(color, vec3(0.2126, 0.7152, 0.0722));
+	return color * (max(lum - u_bloom_threshold, 0.0) / max(lum, 0.0001));
+}
+
+void main()
+{
+	vec2 uv = gl_FragCoord.xy / u_target_size;
+	vec2 texel = 1.0 / vec2(textureSize(u_tex0, 0));
+
+	// four bilinear taps, which cover 4x4 texels of the larger level
+	vec3 color = bright(texture(u_tex0, uv + texel * vec2(-1, -1)).rgb);
+	color += bright(texture(u_tex0, uv + texel * vec2(1, -1)).rgb);
+	color += bright(texture(u_tex0, uv + texel * vec2(-1, 1)).rgb);
+	color += bright(texture(u_tex0, uv + texel * vec2(1, 1)).rgb);
+	io_color = color * 0.25;
+}
@@ -0,0 +1,26 @@
+#version 330 core
+
+// the smaller level, with bilinear filtering
+uniform sampler2D u_tex0;
+uniform float u_bloom_weight;
+uniform vec2 u_target_size;
+
+layout (location = 0) out vec4 io_color;
+
+void main()
+{
+	vec2 uv = gl_FragCoord.xy / u_target_size;
+	vec2 texel = 1.0 / vec2(textureSize(u_tex0, 0));
+
+	// 3x3 tent filter
+	vec3 color = texture(u_tex0, uv).rgb * 4.0;
+	color += texture(u_tex0, uv + texel * vec2(-1, 0)).rgb * 2.0;
+	color += texture(u_tex0, uv + texel * vec2(1, 0)).rgb * 2.0;
+	color += texture(u_tex0, uv + texel * vec2(0, -1)).rgb * 2.0;
+	color += texture(u_tex0, uv + texel * vec2(0, 1)).rgb * 2.0;
+	color += texture(u_tex0, uv + texel * vec2(-1, -1)).rgb;
+	color += texture(u_tex0, uv + texel * vec2(1, -1)).rgb;
+	color += texture(u_tex0, uv + texel * vec2(-1, 1)).rgb;
+	color += texture(u_tex0, uv + texel * vec2(1, 1)).rgb;
+	io_color = vec4(color / 16.0 * u_bloom_weight, 1.0);
+}
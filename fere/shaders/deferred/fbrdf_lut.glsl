@@ -0,0 +1,66 @@
+#version 330 core
+
+// (scale, bias) to F0 of the split-sum approximation, over (n dot v, roughness)
+layout (location = 0) out vec2 io_brdf;
+
+const int SAMPLES = 512;
+const float PI = 3.141592;
+// Keep this same as `BRDF_LUT_SIZE` of `fere::graphics::graphics`
+const float SIZE = 256.0;
+
+vec2 hammersley(int i, int n)
+{
+	uint bits = uint(i);
+	bits = (bits << 16u) | (bits >> 16u);
+	bits = ((bits & 0x55555555u) << 1u) | ((bits & 0xAAAAAAAAu) >> 1u);
+	bits = ((bits & 0x33333333u) << 2u) | ((bits & 0xCCCCCCCCu) >> 2u);
+	bits = ((bits & 0x0F0F0F0Fu) << 4u) | ((bits & 0xF0F0F0F0u) >> 4u);
+	bits = ((bits & 0x00FF00FFu) << 8u) | ((bits & 0xFF00FF00u) >> 8u);
+	return vec2(float(i) / float(n), float(bits) * 2.3283064365386963e-10);
+}
+
+// Around +Z
+vec3 importance_sample_ggx(vec2 xi, float roughness)
+{
+	float a = roughness * roughness;
+	float phi = 2.0 * PI * xi.x;
+	float cos_theta = sqrt((1.0 - xi.y) / (1.0 + (a * a - 1.0) * xi.y));
+	float sin_theta = sqrt(1.0 - cos_theta * cos_theta);
+	return vec3(cos(phi) * sin_theta, sin(phi) * sin_theta, cos_theta);
+}
+
+// k of the image-based lighting, unlike the one of the analytic lights
+float geometry_schlick_ggx(float NdotV, float roughness)
+{
+	float k = roughness * roughness / 2.0;
+	return NdotV / (NdotV * (1.0 - k) + k);
+}
+
+void main()
+{
+	vec2 st = gl_FragCoord.xy / SIZE;
+	float NdotV = max(st.x, 0.001);
+	float roughness = st.y;
+
+	vec3 V = vec3(sqrt(1.0 - NdotV * NdotV), 0.0, NdotV);
+	float scale = 0;
+	float bias = 0;
+	for (int i = 0; i < SAMPLES; i++)
+	{
+		vec3 H = importance_sample_ggx(hammersley(i, SAMPLES), roughness);
+		vec3 L = normalize(2.0 * dot(V, H) * H - V);
+
+		float NdotL = max(L.z, 0.0);
+		float NdotH = max(H.z, 0.0);
+		float VdotH = max(dot(V, H), 0.0);
+		if (NdotL > 0.0)
+		{
+			float G = geometry_schlick_ggx(NdotV, roughness) * geometry_schlick_ggx(NdotL, roughness);
+			float G_vis = G * VdotH / (NdotH * NdotV);
+			float Fc = pow(1.0 - VdotH, 5.0);
+			scale += (1.0 - Fc) * G_vis;
+			bias += Fc * G_vis;
+		}
+	}
+	io_brdf = vec2(scale, bias) / float(SAMPLES);
+}
@@ -6,12 +6,25 @@ struct SLight // could be either point or directional
 	mat4 trans;
 	bool round;
 	float smoothness;
+	vec3 attenuation;
+	float radius;
+	int filter; // 0: none, 1: PCF, 2: PCSS
+	float light_size;
+	float bias;
+	float normal_offset;
 };
-uniform SLight u_lights[1];
+/// Shaded all together in a pass. The shadow map of each light is bound to 8 + i
+uniform SLight u_lights[8];
+uniform int u_light_num;
 uniform vec3 u_cpos;
 uniform int u_shadow;
+
+// for sun lights, only [x, y) along u_cascade_dir from the camera is shaded. disabled if y == 0
+uniform vec3 u_cascade_dir;
+uniform vec2 u_cascade_range;
 /*
-0(pos), 1(norm), 2(bc), 3(roughness), 4(metalness), 5(emission), 6(shadow map), 7(lighting)
+0(pos), 1(norm), 2(bc), 3(roughness), 4(metalness), 5(emission), 7(lighting)
+8 ~ 15(shadow maps)
 */
 
 uniform sampler2D u_tex0;
@@ -20,29 +33,112 @@ uniform sampler2D u_tex2;
 uniform sampler2D u_tex3;
 uniform sampler2D u_tex4;
 uniform sampler2D u_tex5;
-uniform sampler2D u_tex6;
 uniform isampler2D u_tex7;
+uniform sampler2D u_tex8;
+uniform sampler2D u_tex9;
+uniform sampler2D u_tex10;
+uniform sampler2D u_tex11;
+uniform sampler2D u_tex12;
+uniform sampler2D u_tex13;
+uniform sampler2D u_tex14;
+uniform sampler2D u_tex15;
 
 layout (location = 0) out vec4 io_color;
 
 
+const vec2 POISSON_DISK[16] = vec2[](
+	vec2(-0.94201624, -0.39906216), vec2(0.94558609, -0.76890725),
+	vec2(-0.09418410, -0.92938870), vec2(0.34495938, 0.29387760),
+	vec2(-0.91588581, 0.45771432), vec2(-0.81544232, -0.87912464),
+	vec2(-0.38277543, 0.27676845), vec2(0.97484398, 0.75648379),
+	vec2(0.44323325, -0.97511554), vec2(0.53742981, -0.47373420),
+	vec2(-0.26496911, -0.41893023), vec2(0.79197514, 0.19090188),
+	vec2(-0.24188840, 0.99706507), vec2(-0.81409955, 0.91437590),
+	vec2(0.19984126, 0.78641367), vec2(0.14383161, -0.14100790)
+);
+
+// Samplers can't be indexed dynamically in GLSL 3.30
+float shadow_depth(int i, vec2 uv) {
+	if (i == 0) return textureLod(u_tex8, uv, 0).r;
+	if (i == 1) return textureLod(u_tex9, uv, 0).r;
+	if (i == 2) return textureLod(u_tex10, uv, 0).r;
+	if (i == 3) return textureLod(u_tex11, uv, 0).r;
+	if (i == 4) return textureLod(u_tex12, uv, 0).r;
+	if (i == 5) return textureLod(u_tex13, uv, 0).r;
+	if (i == 6) return textureLod(u_tex14, uv, 0).r;
+	return textureLod(u_tex15, uv, 0).r;
+}
+
+// distance from the light to the point at `pc` in the texture space of the shadow map
+float light_distance(int i, vec3 pc) {
+	vec4 w = inverse(u_lights[i].trans) * vec4(pc * 2.0 - 1.0, 1);
+	vec3 P = w.xyz / w.w;
+	if (u_lights[i].wpos.w == 0.0)
+		return -dot(P, normalize(u_lights[i].wpos.xyz));
+	return length(P - u_lights[i].wpos.xyz);
+}
+
+float pcf(int i, vec3 pc, float bias, float radius) {
+	float shadow = 0;
+	for (int k = 0; k < 16; k++)
+	{
+		float depth = shadow_depth(i, pc.xy + POISSON_DISK[k] * radius);
+		shadow += pc.z - bias > depth ? 0.0 : 1.0;
+	}
+	return shadow / 16.0;
+}
+
+float pcss(int i, vec3 pc, float bias, vec2 texel_size) {
+	float light_size = u_lights[i].light_size;
+
+	// Step1: average depth of the blockers
+	float blocker = 0;
+	int blocker_num = 0;
+	for (int k = 0; k < 16; k++)
+	{
+		float depth = shadow_depth(i, pc.xy + POISSON_DISK[k] * light_size);
+		if (pc.z - bias > depth)
+		{
+			blocker += depth;
+			blocker_num++;
+		}
+	}
+	if (blocker_num == 0) return 1;
+	blocker /= blocker_num;
+
+	// Step2: penumbra from the similar triangles
+	float d_receiver = light_distance(i, pc);
+	float d_blocker = light_distance(i, vec3(pc.xy, blocker));
+	float penumbra = light_size * (d_receiver - d_blocker) / max(d_blocker, 0.0001);
+
+	// Step3: filter as wide as the penumbra
+	return pcf(i, pc, bias, max(penumbra, texel_size.x));
+}
+
 // P : position of fragment
-float shade_shadow(vec3 P, vec3 N) {
+float shade_shadow(int i, vec3 P, vec3 N) {
 	if (u_shadow == 0) return 1;
 
-	vec2 texel_size = 1.0 / textureSize(u_tex6, 0);
+	// Every shadow map has the same size
+	vec2 texel_size = 1.0 / textureSize(u_tex8, 0);
+	bool directional = u_lights[i].wpos.w == 0.0;
 
 	// lies in clipping space of light's eye
-	vec4 tpos = u_lights[0].trans * vec4(P, 1);
+	vec4 tpos = u_lights[i].trans * vec4(P + N * u_lights[i].normal_offset, 1);
 	vec3 pc = tpos.xyz/tpos.w;
 
 	float weight = 1.0;
-	if (u_lights[0].round) 
+	if (directional)
+	{
+		if(abs(pc.x) >= 1.0 || abs(pc.y) >= 1.0 || pc.z >= 1.0) 
+			return 1;
+	}
+	else if (u_lights[i].round) 
 	{
 		float dis = pc.x*pc.x + pc.y*pc.y;
 		if (dis > 1)
 			return 0;
-		weight = 1 - pow(dis, 1 + u_lights[0].smoothness * 10); 
+		weight = 1 - pow(dis, 1 + u_lights[i].smoothness * 10); 
 	}
 	else 
 	{
@@ -53,15 +149,22 @@ float shade_shadow(vec3 P, vec3 N) {
 	}
 	pc = pc * 0.5 + 0.5;
 
-	float shadow = 0;
-	vec3 L = normalize(u_lights[0].wpos.xyz - P);
+	float bias = u_lights[i].bias;
+	if (u_lights[i].filter == 0)
+	{
+		return pc.z - bias > shadow_depth(i, pc.xy) ? 0.0 : 1.0;
+	}
+	if (u_lights[i].filter == 2)
+	{
+		return pcss(i, pc, bias, texel_size);
+	}
 
-	for (int i = -2; i <= 2; i++)
+	float shadow = 0;
+	for (int x = -2; x <= 2; x++)
 	{
-		for(int j = -2; j <= 2; j++)
+		for(int y = -2; y <= 2; y++)
 		{
-			float depth = texture(u_tex6, pc.xy + ivec2(i,j) * texel_size).r;
-			float bias = max(0.001 * (1.0) - dot(N, L), 0.0001);
+			float depth = shadow_depth(i, pc.xy + ivec2(x, y) * texel_size);
 			shadow += pc.z - bias > depth ? 0.0 : 1.0;
 		}
 	}
@@ -69,6 +172,19 @@ float shade_shadow(vec3 P, vec3 N) {
 	return shadow / 25.0;
 }
 
+// dis : distance to the light | att : (constant, linear, quadratic) | radius : range of the light, 0 for infinite
+float attenuate(float dis, vec3 att, float radius)
+{
+	float a = 1.0 / max(att.x + att.y * dis + att.z * dis * dis, 0.0001);
+	if (radius > 0.0)
+	{
+		// fades to zero at the boundary of the light volume
+		float r = clamp(1.0 - pow(dis / radius, 4.0), 0.0, 1.0);
+		a *= r * r;
+	}
+	return a;
+}
+
 // PBR formula
 vec3 fresnelSchlick(float cosTheta, vec3 F0)
 {
@@ -107,13 +223,17 @@ float GeometrySmith(vec3 N, vec3 V, vec3 L, float roughness)
 }
 
 // P : position of fragment | N : normal of fragment | P_c : camera position
-// P_l : light position | lc : light color 
+// i : index of the light
 // bc : basecolor | ro : roughness | mt : metalness | em : emission | sd : shadow
-vec3 shade_pbr(vec3 P, vec3 N, vec3 P_c, vec4 P_l, vec3 lc,
+vec3 shade_pbr(vec3 P, vec3 N, vec3 P_c, int i,
  vec3 bc, float ro, float mt, vec3 em, float sd)
 {
+	vec4 P_l = u_lights[i].wpos;
+	vec3 lc = u_lights[i].color;
 	vec3 V = normalize(P_c - P);
-	vec3 L = normalize(P_l.xyz - P); 
+	// w == 0 for directional lights, where P_l is the direction to the light
+	bool directional = P_l.w == 0.0;
+	vec3 L = directional ? normalize(P_l.xyz) : normalize(P_l.xyz - P); 
 	vec3 H = normalize(V + L);
 
 	lc *= sd;
@@ -139,7 +259,7 @@ vec3 shade_pbr(vec3 P, vec3 N, vec3 P_c, vec4 P_l, vec3 lc,
 	float denominator = 4.0 * max(dot(N, V), 0.0) * max(dot(N, L), 0.0);
 	vec3 specular     = numerator / max(denominator, 0.001);  
     
-	float attenuation = 5.0 / (dis * dis);
+	float attenuation = directional ? 1.0 : attenuate(dis, u_lights[i].attenuation, u_lights[i].radius);
     vec3 radiance = lc * attenuation; 
 	return (kD * bc / 3.141592 + specular) * radiance * NDL + em;
 }
@@ -161,20 +281,23 @@ void main()
 	//io_color = vec4(1, 0, 0, 1);
 	//return;
 
-	if(lighting == 1)
+	if(u_cascade_range.y > 0)
 	{
-		float shadow = shade_shadow(wpos, wnormal);
+		float d = dot(wpos - u_cpos, u_cascade_dir);
+		if (d < u_cascade_range.x || d >= u_cascade_range.y)
+			discard;
+	}
 
-		if (shadow < 0.0001)
+	if(lighting == 1)
+	{
+		// The same as shading each light in its own pass
+		vec3 res = vec3(0);
+		for (int i = 0; i < u_light_num; i++)
 		{
-			io_color = vec4(0,0,0,1);
-			return;
+			float shadow = shade_shadow(i, wpos, wnormal);
+			if (shadow < 0.0001) continue;
+			res += shade_pbr(wpos, wnormal, u_cpos, i, bc, ro, mt, em, shadow);
 		}
-
-		vec3 res = shade_pbr(wpos, wnormal, u_cpos, 
-		u_lights[0].wpos, u_lights[0].color, bc, ro, mt, em, shadow);
-
-		res = res / (res + vec3(1.0));
 		io_color = vec4(res, 1.0);
 	}
 	else {
@@ -8,6 +8,8 @@ uniform sampler2D u_tex4;
 uniform sampler2D u_tex5;
 uniform sampler2D u_tex6;
 uniform isampler2D u_tex7;
+// ambient occlusion
+uniform sampler2D u_tex10;
 
 uniform vec3 u_ambient;
 layout (location = 0) out vec4 io_color;
@@ -32,7 +34,8 @@ void main()
 	}
 	else if(lighting == 1)
 	{
-		io_color = vec4(bc * u_ambient, 1);
+		float ao = texelFetch(u_tex10, p, 0).r;
+		io_color = vec4(bc * u_ambient * ao, 1);
 	}
 	else if (lighting == 2 || lighting == 4)
 	{
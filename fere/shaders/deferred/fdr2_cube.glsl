@@ -0,0 +1,191 @@
+#version 330 core
+struct SLight // could be either point or directional
+{
+	vec4 wpos;
+	vec3 color;
+	mat4 trans;
+	bool round;
+	float smoothness;
+	vec3 attenuation;
+	float radius;
+};
+uniform SLight u_lights[1];
+uniform vec3 u_cpos;
+uniform int u_shadow;
+uniform float u_far;
+/*
+0(pos), 1(norm), 2(bc), 3(roughness), 4(metalness), 5(emission), 6(cube shadow map), 7(lighting)
+*/
+
+uniform sampler2D u_tex0;
+uniform sampler2D u_tex1;
+uniform sampler2D u_tex2;
+uniform sampler2D u_tex3;
+uniform sampler2D u_tex4;
+uniform sampler2D u_tex5;
+uniform samplerCube u_tex6;
+uniform isampler2D u_tex7;
+
+layout (location = 0) out vec4 io_color;
+
+const vec3 SAMPLE_OFFSETS[20] = vec3[](
+	vec3( 1,  1,  1), vec3( 1, -1,  1), vec3(-1, -1,  1), vec3(-1,  1,  1),
+	vec3( 1,  1, -1), vec3( 1, -1, -1), vec3(-1, -1, -1), vec3(-1,  1, -1),
+	vec3( 1,  1,  0), vec3( 1, -1,  0), vec3(-1, -1,  0), vec3(-1,  1,  0),
+	vec3( 1,  0,  1), vec3(-1,  0,  1), vec3( 1,  0, -1), vec3(-1,  0, -1),
+	vec3( 0,  1,  1), vec3( 0, -1,  1), vec3( 0, -1, -1), vec3( 0,  1, -1)
+);
+
+
+// P : position of fragment
+float shade_shadow(vec3 P, vec3 N) {
+	if (u_shadow == 0) return 1;
+
+	vec3 D = P - u_lights[0].wpos.xyz;
+	float dis = length(D);
+	if (dis >= u_far) return 0;
+
+	vec3 L = -D / dis;
+	float bias = max(0.005 * (1.0 - dot(N, L)), 0.0005);
+	float current = dis / u_far - bias;
+
+	// sample around the direction to soften the edge
+	float disk = (1.0 + dis / u_far) * 0.01;
+	float shadow = 0;
+	for (int i = 0; i < 20; i++)
+	{
+		float depth = texture(u_tex6, normalize(D) + SAMPLE_OFFSETS[i] * disk).r;
+		shadow += current > depth ? 0.0 : 1.0;
+	}
+
+	return shadow / 20.0;
+}
+
+// dis : distance to the light | att : (constant, linear, quadratic) | radius : range of the light, 0 for infinite
+float attenuate(float dis, vec3 att, float radius)
+{
+	float a = 1.0 / max(att.x + att.y * dis + att.z * dis * dis, 0.0001);
+	if (radius > 0.0)
+	{
+		// fades to zero at the boundary of the light volume
+		float r = clamp(1.0 - pow(dis / radius, 4.0), 0.0, 1.0);
+		a *= r * r;
+	}
+	return a;
+}
+
+// PBR formula
+vec3 fresnelSchlick(float cosTheta, vec3 F0)
+{
+    return F0 + (1.0 - F0) * pow(1.0 - cosTheta, 5.0);
+} 
+float DistributionGGX(vec3 N, vec3 H, float roughness)
+{
+    float a      = roughness*roughness;
+    float a2     = a*a;
+    float NdotH  = max(dot(N, H), 0.0);
+    float NdotH2 = NdotH*NdotH;
+	
+    float num   = a2;
+    float denom = (NdotH2 * (a2 - 1.0) + 1.0);
+    denom = 3.141592 * denom * denom;
+    return num / denom;
+}
+float GeometrySchlickGGX(float NdotV, float roughness)
+{
+    float r = (roughness + 1.0);
+    float k = (r*r) / 8.0;
+
+    float num   = NdotV;
+    float denom = NdotV * (1.0 - k) + k;
+	
+    return num / denom;
+}
+float GeometrySmith(vec3 N, vec3 V, vec3 L, float roughness)
+{
+    float NdotV = max(dot(N, V), 0.0);
+    float NdotL = max(dot(N, L), 0.0);
+    float ggx2  = GeometrySchlickGGX(NdotV, roughness);
+    float ggx1  = GeometrySchlickGGX(NdotL, roughness);
+	
+    return ggx1 * ggx2;
+}
+
+// P : position of fragment | N : normal of fragment | P_c : camera position
+// P_l : light position | lc : light color 
+// bc : basecolor | ro : roughness | mt : metalness | em : emission | sd : shadow
+vec3 shade_pbr(vec3 P, vec3 N, vec3 P_c, vec4 P_l, vec3 lc,
+ vec3 bc, float ro, float mt, vec3 em, float sd)
+{
+	vec3 V = normalize(P_c - P);
+	vec3 L = normalize(P_l.xyz - P); 
+	vec3 H = normalize(V + L);
+
+	lc *= sd;
+	
+	float dis = length(P_l.xyz - P);
+	float NDL = dot(N, L);
+	float NDV = dot(N, V);
+
+	if(NDL <= 0.0 || NDV <= 0.0) return vec3(0);
+
+	float NDF = DistributionGGX(N, H, ro);
+	float G = GeometrySmith(N, V, L, ro);
+
+	vec3 F0 = vec3(0.04); 
+	F0 = mix(F0, bc, mt);
+	vec3 F = fresnelSchlick(NDL, F0);
+	
+	vec3 kS = F;
+    vec3 kD = vec3(1.0) - kS;
+    kD *= 1.0 - mt;
+
+	vec3 numerator    = NDF * G * F;
+	float denominator = 4.0 * max(dot(N, V), 0.0) * max(dot(N, L), 0.0);
+	vec3 specular     = numerator / max(denominator, 0.001);  
+    
+	float attenuation = attenuate(dis, u_lights[0].attenuation, u_lights[0].radius);
+    vec3 radiance = lc * attenuation; 
+	return (kD * bc / 3.141592 + specular) * radiance * NDL + em;
+}
+
+void main()
+{
+	vec2 q = gl_FragCoord.xy;
+	ivec2 p = ivec2(int(q[0]), int(q[1]));
+
+    vec3 wpos = texelFetch(u_tex0, p, 0).rgb;
+    vec3 wnormal = normalize(texelFetch(u_tex1, p, 0).rgb);
+
+    vec3 bc = texelFetch(u_tex2, p, 0).rgb;
+    float ro = texelFetch(u_tex3, p, 0).r;
+	float mt = texelFetch(u_tex4, p, 0).r;
+	vec3 em = texelFetch(u_tex5, p, 0).rgb;
+	int lighting = texelFetch(u_tex7, p, 0).r;
+
+	//io_color = vec4(1, 0, 0, 1);
+	//return;
+
+	if(lighting == 1)
+	{
+		float shadow = shade_shadow(wpos, wnormal);
+
+		if (shadow < 0.0001)
+		{
+			io_color = vec4(0,0,0,1);
+			return;
+		}
+
+		vec3 res = shade_pbr(wpos, wnormal, u_cpos, 
+		u_lights[0].wpos, u_lights[0].color, bc, ro, mt, em, shadow);
+
+		io_color = vec4(res, 1.0);
+	}
+	else {
+		discard;
+	};
+
+}
+
+
+
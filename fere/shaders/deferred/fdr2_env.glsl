@@ -0,0 +1,34 @@
+#version 330 core
+
+uniform sampler2D u_tex0;
+uniform sampler2D u_tex1;
+uniform sampler2D u_tex2;
+uniform sampler2D u_tex3;
+uniform sampler2D u_tex4;
+uniform sampler2D u_tex5;
+uniform sampler2D u_tex6;
+uniform isampler2D u_tex7;
+// environment
+uniform samplerCube u_tex8;
+// ambient occlusion
+uniform sampler2D u_tex10;
+
+uniform float u_sky_intensity;
+// the level of mipmap which is blurry enough to be irradiance
+uniform float u_sky_lod;
+
+layout (location = 0) out vec4 io_color;
+
+void main()
+{
+	ivec2 p = ivec2(gl_FragCoord.xy);
+	int lighting = texelFetch(u_tex7, p, 0).r;
+	if (lighting != 1) discard;
+
+	vec3 wnormal = normalize(texelFetch(u_tex1, p, 0).rgb);
+	vec3 bc = texelFetch(u_tex2, p, 0).rgb;
+	float ao = texelFetch(u_tex10, p, 0).r;
+
+	vec3 env = textureLod(u_tex8, wnormal, u_sky_lod).rgb * u_sky_intensity;
+	io_color = vec4(bc * env * ao, 1);
+}
@@ -0,0 +1,100 @@
+#version 330 core
+uniform vec3 u_cpos;
+
+uniform sampler2D u_tex0;
+uniform sampler2D u_tex1;
+uniform sampler2D u_tex2;
+uniform sampler2D u_tex3;
+uniform sampler2D u_tex4;
+uniform sampler2D u_tex5;
+uniform sampler2D u_tex6;
+uniform isampler2D u_tex7;
+// prefiltered specular
+uniform samplerCube u_tex8;
+// BRDF lookup table
+uniform sampler2D u_tex9;
+// ambient occlusion
+uniform sampler2D u_tex10;
+// screen-space reflections, which replace the specular where they hit
+uniform sampler2D u_tex11;
+
+// irradiance
+uniform vec3 u_sh_coeff[18];
+uniform float u_ibl_intensity;
+// the number of the mipmap levels of the specular
+uniform float u_ibl_levels;
+
+layout (location = 0) out vec4 io_color;
+
+const float PI = 3.141592;
+
+vec3 spherical_harmonics(vec3 sh_coeff[18], vec3 p, mat3 table)
+{
+    vec3 result = vec3(0);
+
+    result += sh_coeff[0];
+
+    result += sh_coeff[1] * p[1];
+    result += sh_coeff[2] * p[2];
+    result += sh_coeff[3] * p[0];
+
+    result += sh_coeff[4] * table[0][1];
+    result += sh_coeff[5] * table[1][2];
+    result += sh_coeff[6] * (3 * table[2][2] - 1);
+    result += sh_coeff[7] * table[0][2];
+    result += sh_coeff[8] * (table[0][0] - table[1][1]);
+
+    result += sh_coeff[9] * table[0][1] * (table[0][0] - table[1][1]);
+    result += sh_coeff[10] * table[1][2] * (3 * table[0][0] - table[1][1]);
+    result += sh_coeff[11] * table[0][1] * (7 * table[2][2] - 1);
+    result += sh_coeff[12] * table[1][2] * (7 * table[2][2] - 3);
+    result += sh_coeff[13] * (table[2][2] * (35 * table[2][2] - 30) + 3);
+    result += sh_coeff[14] * table[0][2] * (7 * table[2][2] - 3);
+    result += sh_coeff[15] * (table[0][0] - table[1][1]) * (7 * table[2][2] - 1);
+    result += sh_coeff[16] * table[0][2] * (table[0][0] - 3 * table[1][1]);
+    result += sh_coeff[17] * (table[0][0] * (table[0][0] - 3 * table[1][1]) - table[1][1] * (3 * table[0][0] - table[1][1]));
+
+	return result;
+}
+
+// Schlick with the roughness, for the light from every direction
+vec3 fresnel_schlick_roughness(float cos_theta, vec3 F0, float roughness)
+{
+	return F0 + (max(vec3(1.0 - roughness), F0) - F0) * pow(1.0 - cos_theta, 5.0);
+}
+
+void main()
+{
+	ivec2 p = ivec2(gl_FragCoord.xy);
+	int lighting = texelFetch(u_tex7, p, 0).r;
+	if (lighting != 1) discard;
+
+	vec3 wpos = texelFetch(u_tex0, p, 0).rgb;
+	vec3 N = normalize(texelFetch(u_tex1, p, 0).rgb);
+	vec3 bc = texelFetch(u_tex2, p, 0).rgb;
+	float ro = texelFetch(u_tex3, p, 0).r;
+	float mt = texelFetch(u_tex4, p, 0).r;
+	float ao = texelFetch(u_tex10, p, 0).r;
+	float miss = 1.0 - texelFetch(u_tex11, p, 0).a;
+
+	vec3 V = normalize(u_cpos - wpos);
+	vec3 R = reflect(-V, N);
+	float NdotV = max(dot(N, V), 0.0001);
+
+	vec3 F0 = mix(vec3(0.04), bc, mt);
+	vec3 F = fresnel_schlick_roughness(NdotV, F0, ro);
+	vec3 kD = (1.0 - F) * (1.0 - mt);
+
+	mat3 table;
+	for (int i = 0; i < 3; i++)
+		for (int j = 0; j < 3; j++)
+			table[i][j] = N[i] * N[j];
+	vec3 irradiance = max(spherical_harmonics(u_sh_coeff, N, table), vec3(0));
+	vec3 diffuse = irradiance * bc / PI;
+
+	vec3 prefiltered = textureLod(u_tex8, R, ro * (u_ibl_levels - 1.0)).rgb;
+	vec2 brdf = texture(u_tex9, vec2(NdotV, ro)).rg;
+	vec3 specular = prefiltered * (F0 * brdf.x + brdf.y);
+
+	io_color = vec4((kD * diffuse + specular * miss) * ao * u_ibl_intensity, 1);
+}
@@ -40,6 +40,8 @@ uniform sampler2D u_tex4;
 uniform sampler2D u_tex5;
 uniform sampler2D u_tex6;
 uniform isampler2D u_tex7;
+// ambient occlusion
+uniform sampler2D u_tex10;
 
 uniform sampler3D u_tex8;
 uniform sampler3D u_tex9;
@@ -220,7 +222,7 @@ void main()
 		float depth;
 
 		irradiance_volume(wpos, wnormal, illumination, depth);
-		illumination *= 10.0 * u_lv.weight;
+		illumination *= 10.0 * u_lv.weight * texelFetch(u_tex10, p, 0).r;
 		io_color = vec4(illumination, 1.0);
 	}
 	else if (lighting == 4) {
@@ -0,0 +1,68 @@
+#version 330 core
+uniform vec3 u_cpos;
+
+uniform sampler2D u_tex0;
+uniform sampler2D u_tex1;
+uniform sampler2D u_tex2;
+uniform sampler2D u_tex3;
+uniform sampler2D u_tex4;
+uniform sampler2D u_tex5;
+uniform sampler2D u_tex6;
+uniform isampler2D u_tex7;
+// prefiltered specular of the probe
+uniform samplerCube u_tex8;
+// BRDF lookup table
+uniform sampler2D u_tex9;
+// ambient occlusion
+uniform sampler2D u_tex10;
+// screen-space reflections; the probe fills where they miss
+uniform sampler2D u_tex11;
+
+uniform vec3 u_probe_pos;
+uniform vec3 u_box_min;
+uniform vec3 u_box_max;
+uniform float u_ibl_intensity;
+// the number of the mipmap levels of the specular
+uniform float u_ibl_levels;
+
+layout (location = 0) out vec4 io_color;
+
+// Intersects the ray from `p` inside of the box along `r` with the box,
+// and returns the direction to there from the probe.
+vec3 box_project(vec3 p, vec3 r)
+{
+	vec3 t_max = (u_box_max - p) / r;
+	vec3 t_min = (u_box_min - p) / r;
+	vec3 t_far = max(t_max, t_min);
+	float t = min(min(t_far.x, t_far.y), t_far.z);
+	return p + r * t - u_probe_pos;
+}
+
+void main()
+{
+	ivec2 p = ivec2(gl_FragCoord.xy);
+	int lighting = texelFetch(u_tex7, p, 0).r;
+	if (lighting != 1) discard;
+
+	vec3 wpos = texelFetch(u_tex0, p, 0).rgb;
+	if (any(lessThan(wpos, u_box_min)) || any(greaterThan(wpos, u_box_max))) discard;
+
+	vec3 N = normalize(texelFetch(u_tex1, p, 0).rgb);
+	vec3 bc = texelFetch(u_tex2, p, 0).rgb;
+	float ro = texelFetch(u_tex3, p, 0).r;
+	float mt = texelFetch(u_tex4, p, 0).r;
+	float ao = texelFetch(u_tex10, p, 0).r;
+	float miss = 1.0 - texelFetch(u_tex11, p, 0).a;
+
+	vec3 V = normalize(u_cpos - wpos);
+	vec3 R = reflect(-V, N);
+	float NdotV = max(dot(N, V), 0.0001);
+
+	vec3 F0 = mix(vec3(0.04), bc, mt);
+
+	vec3 prefiltered = textureLod(u_tex8, box_project(wpos, R), ro * (u_ibl_levels - 1.0)).rgb;
+	vec2 brdf = texture(u_tex9, vec2(NdotV, ro)).rg;
+	vec3 specular = prefiltered * (F0 * brdf.x + brdf.y);
+
+	io_color = vec4(specular * ao * miss * u_ibl_intensity, 1);
+}
@@ -0,0 +1,145 @@
+#version 330 core
+uniform vec3 u_cpos;
+/*
+0(pos), 1(norm), 2(bc), 3(roughness), 4(metalness), 5(emission), 7(lighting)
+8(lights), 9(tile grid), 10(light list)
+*/
+
+uniform sampler2D u_tex0;
+uniform sampler2D u_tex1;
+uniform sampler2D u_tex2;
+uniform sampler2D u_tex3;
+uniform sampler2D u_tex4;
+uniform sampler2D u_tex5;
+uniform isampler2D u_tex7;
+
+// each row is a light: (pos, radius), (color, _), (attenuation, _)
+uniform sampler2D u_tex8;
+// (offset, count) in the light list for each tile
+uniform isampler2D u_tex9;
+uniform isampler2D u_tex10;
+
+// Keep these same as `fere::graphics::graphics::tiled`
+const int TILE_SIZE = 16;
+const int LIST_WIDTH = 4096;
+
+layout (location = 0) out vec4 io_color;
+
+// dis : distance to the light | att : (constant, linear, quadratic) | radius : range of the light, 0 for infinite
+float attenuate(float dis, vec3 att, float radius)
+{
+	float a = 1.0 / max(att.x + att.y * dis + att.z * dis * dis, 0.0001);
+	if (radius > 0.0)
+	{
+		// fades to zero at the boundary of the light volume
+		float r = clamp(1.0 - pow(dis / radius, 4.0), 0.0, 1.0);
+		a *= r * r;
+	}
+	return a;
+}
+
+// PBR formula
+vec3 fresnelSchlick(float cosTheta, vec3 F0)
+{
+    return F0 + (1.0 - F0) * pow(1.0 - cosTheta, 5.0);
+}
+float DistributionGGX(vec3 N, vec3 H, float roughness)
+{
+    float a      = roughness*roughness;
+    float a2     = a*a;
+    float NdotH  = max(dot(N, H), 0.0);
+    float NdotH2 = NdotH*NdotH;
+
+    float num   = a2;
+    float denom = (NdotH2 * (a2 - 1.0) + 1.0);
+    denom = 3.141592 * denom * denom;
+    return num / denom;
+}
+float GeometrySchlickGGX(float NdotV, float roughness)
+{
+    float r = (roughness + 1.0);
+    float k = (r*r) / 8.0;
+
+    float num   = NdotV;
+    float denom = NdotV * (1.0 - k) + k;
+
+    return num / denom;
+}
+float GeometrySmith(vec3 N, vec3 V, vec3 L, float roughness)
+{
+    float NdotV = max(dot(N, V), 0.0);
+    float NdotL = max(dot(N, L), 0.0);
+    float ggx2  = GeometrySchlickGGX(NdotV, roughness);
+    float ggx1  = GeometrySchlickGGX(NdotL, roughness);
+
+    return ggx1 * ggx2;
+}
+
+// P : position of fragment | N : normal of fragment | P_c : camera position
+// P_l : light position | lc : light color | att, radius : attenuation of the light
+// bc : basecolor | ro : roughness | mt : metalness
+vec3 shade_pbr(vec3 P, vec3 N, vec3 P_c, vec3 P_l, vec3 lc, vec3 att, float radius,
+ vec3 bc, float ro, float mt)
+{
+	float dis = length(P_l - P);
+	if (dis >= radius) return vec3(0);
+
+	vec3 V = normalize(P_c - P);
+	vec3 L = normalize(P_l - P);
+	vec3 H = normalize(V + L);
+
+	float NDL = dot(N, L);
+	float NDV = dot(N, V);
+
+	if(NDL <= 0.0 || NDV <= 0.0) return vec3(0);
+
+	float NDF = DistributionGGX(N, H, ro);
+	float G = GeometrySmith(N, V, L, ro);
+
+	vec3 F0 = vec3(0.04);
+	F0 = mix(F0, bc, mt);
+	vec3 F = fresnelSchlick(NDL, F0);
+
+	vec3 kS = F;
+    vec3 kD = vec3(1.0) - kS;
+    kD *= 1.0 - mt;
+
+	vec3 numerator    = NDF * G * F;
+	float denominator = 4.0 * max(dot(N, V), 0.0) * max(dot(N, L), 0.0);
+	vec3 specular     = numerator / max(denominator, 0.001);
+
+	float attenuation = attenuate(dis, att, radius);
+    vec3 radiance = lc * attenuation;
+	return (kD * bc / 3.141592 + specular) * radiance * NDL;
+}
+
+void main()
+{
+	ivec2 p = ivec2(gl_FragCoord.xy);
+
+	int lighting = texelFetch(u_tex7, p, 0).r;
+	if (lighting != 1) discard;
+
+    vec3 wpos = texelFetch(u_tex0, p, 0).rgb;
+    vec3 wnormal = normalize(texelFetch(u_tex1, p, 0).rgb);
+
+    vec3 bc = texelFetch(u_tex2, p, 0).rgb;
+    float ro = texelFetch(u_tex3, p, 0).r;
+	float mt = texelFetch(u_tex4, p, 0).r;
+
+	ivec2 tile = texelFetch(u_tex9, p / TILE_SIZE, 0).rg;
+
+	vec3 res = vec3(0);
+	for (int i = tile.x; i < tile.x + tile.y; i++)
+	{
+		int index = texelFetch(u_tex10, ivec2(i % LIST_WIDTH, i / LIST_WIDTH), 0).r;
+		vec4 pos = texelFetch(u_tex8, ivec2(0, index), 0);
+		vec3 color = texelFetch(u_tex8, ivec2(1, index), 0).rgb;
+		vec3 att = texelFetch(u_tex8, ivec2(2, index), 0).rgb;
+
+		// same as shading each light in its own light volume
+		vec3 x = shade_pbr(wpos, wnormal, u_cpos, pos.xyz, color, att, pos.w, bc, ro, mt);
+		res += x;
+	}
+	io_color = vec4(res, 1.0);
+}
@@ -0,0 +1,35 @@
+#version 330 core
+// (accumulated in-scattering, transmittance) of the froxels
+uniform sampler3D u_tex0;
+// depth of the G-buffer
+uniform sampler2D u_tex1;
+
+// projection * view of the camera, inverted
+uniform mat4 u_inv_camera_trans;
+uniform vec3 u_cpos;
+// [near, far) of the froxels along the view
+uniform vec2 u_froxel_range;
+uniform ivec3 u_froxel_size;
+
+// blended as (scene * transmittance + in-scattering)
+layout (location = 0) out vec4 io_color;
+
+void main()
+{
+	vec2 uv = gl_FragCoord.xy / vec2(textureSize(u_tex1, 0));
+	float depth = texture(u_tex1, uv).r;
+
+	// The sky is at the end of the froxels
+	float dis = u_froxel_range.y;
+	if (depth < 1.0)
+	{
+		vec4 w = u_inv_camera_trans * vec4(vec3(uv, depth) * 2.0 - 1.0, 1);
+		dis = length(w.xyz / w.w - u_cpos);
+	}
+
+	float n = float(u_froxel_size.z);
+	float s = log(max(dis, u_froxel_range.x) / u_froxel_range.x) / log(u_froxel_range.y / u_froxel_range.x);
+	// each slice holds the sum to its far side
+	float z = clamp(s - 0.5 / n, 0.5 / n, 1.0 - 0.5 / n);
+	io_color = texture(u_tex0, vec3(uv, z));
+}
@@ -0,0 +1,36 @@
+#version 330 core
+// (in-scattering, extinction) of the froxels
+uniform sampler3D u_tex0;
+
+// [near, far) of the froxels along the view
+uniform vec2 u_froxel_range;
+uniform ivec3 u_froxel_size;
+uniform int u_slice;
+
+// (accumulated in-scattering, transmittance) from the camera to the far side of the slice
+layout (location = 0) out vec4 io_integrated;
+
+float slice_distance(float z)
+{
+	return u_froxel_range.x * pow(u_froxel_range.y / u_froxel_range.x, z / float(u_froxel_size.z));
+}
+
+void main()
+{
+	ivec2 p = ivec2(gl_FragCoord.xy);
+	vec3 scattering = vec3(0);
+	float transmittance = 1.0;
+
+	// Every slice walks from the camera by itself, as there's no compute shader to carry the sum.
+	for (int i = 0; i <= u_slice; i++)
+	{
+		vec4 froxel = texelFetch(u_tex0, ivec3(p, i), 0);
+		float extinction = max(froxel.a, 0.000001);
+		float step_transmittance = exp(-extinction * (slice_distance(float(i + 1)) - slice_distance(float(i))));
+
+		// analytic integration of the in-scattering over the froxel
+		scattering += transmittance * froxel.rgb * (1.0 - step_transmittance) / extinction;
+		transmittance *= step_transmittance;
+	}
+	io_integrated = vec4(scattering, transmittance);
+}
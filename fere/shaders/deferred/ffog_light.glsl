@@ -0,0 +1,110 @@
+#version 330 core
+struct SLight // could be either point or directional
+{
+	vec4 wpos;
+	vec3 color;
+	mat4 trans;
+	bool round;
+	float smoothness;
+	vec3 attenuation;
+	float radius;
+	int filter; // 0: none, 1: PCF, 2: PCSS
+	float light_size;
+	float bias;
+	float normal_offset;
+};
+uniform SLight u_lights[1];
+uniform int u_shadow;
+
+// shadow map
+uniform sampler2D u_tex6;
+
+// projection * view of the camera, inverted
+uniform mat4 u_inv_camera_trans;
+uniform vec3 u_cpos;
+// [near, far) of the froxels along the view
+uniform vec2 u_froxel_range;
+uniform ivec3 u_froxel_size;
+uniform int u_slice;
+
+// bounds of the chamber
+uniform vec3 u_box_min;
+uniform vec3 u_box_max;
+uniform float u_fog_density;
+uniform float u_fog_height_falloff;
+uniform vec3 u_fog_albedo;
+
+// (in-scattering, extinction) per unit length
+layout (location = 0) out vec4 io_scattering;
+
+const float PI = 3.141592;
+
+// the world position of the center of the current froxel
+vec3 froxel_pos()
+{
+	vec3 f = vec3(gl_FragCoord.xy, float(u_slice) + 0.5) / vec3(u_froxel_size);
+	vec4 w = u_inv_camera_trans * vec4(f.xy * 2.0 - 1.0, 1.0, 1.0);
+	vec3 dir = normalize(w.xyz / w.w - u_cpos);
+	return u_cpos + dir * u_froxel_range.x * pow(u_froxel_range.y / u_froxel_range.x, f.z);
+}
+
+// dis : distance to the light | att : (constant, linear, quadratic) | radius : range of the light, 0 for infinite
+float attenuate(float dis, vec3 att, float radius)
+{
+	float a = 1.0 / max(att.x + att.y * dis + att.z * dis * dis, 0.0001);
+	if (radius > 0.0)
+	{
+		// fades to zero at the boundary of the light volume
+		float r = clamp(1.0 - pow(dis / radius, 4.0), 0.0, 1.0);
+		a *= r * r;
+	}
+	return a;
+}
+
+// Same as the hard shadow of the surfaces, since each froxel is already a blurry sample
+float shade_shadow(vec3 P)
+{
+	vec4 tpos = u_lights[0].trans * vec4(P, 1);
+	vec3 pc = tpos.xyz / tpos.w;
+
+	float weight = 1.0;
+	if (u_lights[0].wpos.w == 0.0)
+	{
+		if (abs(pc.x) >= 1.0 || abs(pc.y) >= 1.0 || pc.z >= 1.0)
+			return 1.0;
+	}
+	else
+	{
+		if (abs(pc.x) >= 1.0 || abs(pc.y) >= 1.0 || pc.z <= 0.0)
+			return 0.0;
+		if (u_lights[0].round)
+		{
+			float dis = pc.x * pc.x + pc.y * pc.y;
+			if (dis > 1.0)
+				return 0.0;
+			weight = 1.0 - pow(dis, 1.0 + u_lights[0].smoothness * 10.0);
+		}
+	}
+	if (u_shadow == 0) return weight;
+
+	pc = pc * 0.5 + 0.5;
+	return pc.z - u_lights[0].bias > texture(u_tex6, pc.xy).r ? 0.0 : weight;
+}
+
+void main()
+{
+	vec3 P = froxel_pos();
+	if (any(lessThan(P, u_box_min)) || any(greaterThanEqual(P, u_box_max))) discard;
+
+	float visibility = shade_shadow(P);
+	if (visibility <= 0.0) discard;
+
+	bool directional = u_lights[0].wpos.w == 0.0;
+	float attenuation = directional ? 1.0 :
+		attenuate(length(u_lights[0].wpos.xyz - P), u_lights[0].attenuation, u_lights[0].radius);
+
+	float extinction = u_fog_density * exp(-u_fog_height_falloff * (P.z - u_box_min.z));
+	// isotropic phase function
+	vec3 radiance = u_lights[0].color * attenuation * visibility / (4.0 * PI);
+	io_scattering = vec4(u_fog_albedo * extinction * radiance, 0.0);
+}
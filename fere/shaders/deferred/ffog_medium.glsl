@@ -0,0 +1,38 @@
+#version 330 core
+// projection * view of the camera, inverted
+uniform mat4 u_inv_camera_trans;
+uniform vec3 u_cpos;
+// [near, far) of the froxels along the view
+uniform vec2 u_froxel_range;
+uniform ivec3 u_froxel_size;
+uniform int u_slice;
+
+// bounds of the chamber
+uniform vec3 u_box_min;
+uniform vec3 u_box_max;
+uniform float u_fog_density;
+uniform float u_fog_height_falloff;
+uniform vec3 u_fog_albedo;
+uniform vec3 u_ambient;
+
+// (in-scattering, extinction) per unit length
+layout (location = 0) out vec4 io_scattering;
+
+// the world position of the center of the current froxel
+vec3 froxel_pos()
+{
+	vec3 f = vec3(gl_FragCoord.xy, float(u_slice) + 0.5) / vec3(u_froxel_size);
+	vec4 w = u_inv_camera_trans * vec4(f.xy * 2.0 - 1.0, 1.0, 1.0);
+	vec3 dir = normalize(w.xyz / w.w - u_cpos);
+	return u_cpos + dir * u_froxel_range.x * pow(u_froxel_range.y / u_froxel_range.x, f.z);
+}
+
+void main()
+{
+	vec3 P = froxel_pos();
+	if (any(lessThan(P, u_box_min)) || any(greaterThanEqual(P, u_box_max))) discard;
+
+	float extinction = u_fog_density * exp(-u_fog_height_falloff * (P.z - u_box_min.z));
+	// the ambient light comes from every direction, so the phase function integrates to 1
+	io_scattering = vec4(u_fog_albedo * extinction * u_ambient, extinction);
+}
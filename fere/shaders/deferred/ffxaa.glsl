@@ -0,0 +1,51 @@
+#version 330 core
+
+// hdr color, with the bilinear filter
+uniform sampler2D u_tex0;
+
+layout (location = 0) out vec3 io_color;
+
+const float SPAN_MAX = 8.0;
+const float REDUCE_MUL = 1.0 / 8.0;
+const float REDUCE_MIN = 1.0 / 128.0;
+
+// Edges are found on the compressed luminance, as they look after the tone mapping
+float luma(vec3 c)
+{
+	float l = dot(c, vec3(0.299, 0.587, 0.114));
+	return l / (1.0 + l);
+}
+
+vec3 fetch(vec2 uv)
+{
+	return texture(u_tex0, uv).rgb;
+}
+
+void main()
+{
+	vec2 texel = 1.0 / vec2(textureSize(u_tex0, 0));
+	vec2 uv = gl_FragCoord.xy * texel;
+
+	vec3 m = fetch(uv);
+	float lm = luma(m);
+	float lnw = luma(fetch(uv + vec2(-0.5, -0.5) * texel));
+	float lne = luma(fetch(uv + vec2(0.5, -0.5) * texel));
+	float lsw = luma(fetch(uv + vec2(-0.5, 0.5) * texel));
+	float lse = luma(fetch(uv + vec2(0.5, 0.5) * texel));
+
+	float lmin = min(lm, min(min(lnw, lne), min(lsw, lse)));
+	float lmax = max(lm, max(max(lnw, lne), max(lsw, lse)));
+
+	// Perpendicular to the gradient, along the edge
+	vec2 dir = vec2(-((lnw + lne) - (lsw + lse)), (lnw + lsw) - (lne + lse));
+	float reduce = max((lnw + lne + lsw + lse) * 0.25 * REDUCE_MUL, REDUCE_MIN);
+	float rcp = 1.0 / (min(abs(dir.x), abs(dir.y)) + reduce);
+	dir = clamp(dir * rcp, vec2(-SPAN_MAX), vec2(SPAN_MAX)) * texel;
+
+	vec3 a = 0.5 * (fetch(uv + dir * (1.0 / 3.0 - 0.5)) + fetch(uv + dir * (2.0 / 3.0 - 0.5)));
+	vec3 b = a * 0.5 + 0.25 * (fetch(uv - dir * 0.5) + fetch(uv + dir * 0.5));
+
+	// The wider one went across another edge
+	float lb = luma(b);
+	io_color = (lb < lmin || lb > lmax) ? a : b;
+}
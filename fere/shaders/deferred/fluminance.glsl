@@ -0,0 +1,16 @@
+#version 330 core
+
+// HDR lighting
+uniform sampler2D u_tex0;
+
+layout (location = 0) out float io_luminance;
+
+// Log-luminance of the screen, downsampled to this target
+void main()
+{
+	ivec2 scale = textureSize(u_tex0, 0) / 256;
+	ivec2 p = ivec2(gl_FragCoord.xy) * max(scale, ivec2(1));
+	vec3 color = texelFetch(u_tex0, p, 0).rgb;
+	float lum = dot(color, vec3(0.2126, 0.7152, 0.0722));
+	io_luminance = log(max(lum, 0.0001));
+}
@@ -0,0 +1,32 @@
+#version 330 core
+
+// depth of the G-buffer
+uniform sampler2D u_tex0;
+
+// the inverse of the current projection * view, and the previous one, both with the jitter
+uniform mat4 u_inv_camera_trans;
+uniform mat4 u_prev_camera_trans;
+// (current, previous) jitter in the normalized device coordinates
+uniform vec4 u_jitter;
+
+// (current - previous) position in the uv space
+layout (location = 0) out vec2 io_motion;
+
+void main()
+{
+	ivec2 p = ivec2(gl_FragCoord.xy);
+	vec2 ndc = (vec2(p) + 0.5) / vec2(textureSize(u_tex0, 0)) * 2.0 - 1.0;
+	float depth = texelFetch(u_tex0, p, 0).r;
+
+	// Only the camera moves here, which is what the background takes.
+	// The objects write their own motion over it, in `fmotion_object.glsl`.
+	// The background (depth = 1) is reprojected as a point on the far plane.
+	vec4 wpos = u_inv_camera_trans * vec4(ndc, depth * 2.0 - 1.0, 1.0);
+	vec4 prev = u_prev_camera_trans * vec4(wpos.xyz / wpos.w, 1.0);
+	if (prev.w <= 0.0)
+	{
+		io_motion = vec2(0);
+		return;
+	}
+	io_motion = ((ndc - u_jitter.xy) - (prev.xy / prev.w - u_jitter.zw)) * 0.5;
+}
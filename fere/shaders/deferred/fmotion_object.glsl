@@ -0,0 +1,21 @@
+#version 330 core
+in vec4 current;
+in vec4 previous;
+
+// (current, previous) jitter in the normalized device coordinates
+uniform vec4 u_jitter;
+
+// (current - previous) position in the uv space
+layout (location = 0) out vec2 io_motion;
+
+void main()
+{
+	if (previous.w <= 0.0)
+	{
+		io_motion = vec2(0);
+		return;
+	}
+	vec2 c = current.xy / current.w - u_jitter.xy;
+	vec2 p = previous.xy / previous.w - u_jitter.zw;
+	io_motion = (c - p) * 0.5;
+}
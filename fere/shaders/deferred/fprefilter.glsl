@@ -0,0 +1,97 @@
+#version 330 core
+
+// environment, with mipmaps
+uniform samplerCube u_tex0;
+
+// GL_TEXTURE_CUBE_MAP_POSITIVE_X + u_cube_face
+uniform int u_cube_face;
+uniform float u_prefilter_roughness;
+// the size of the level being rendered
+uniform vec2 u_target_size;
+
+layout (location = 0) out vec3 io_color;
+
+const int SAMPLES = 128;
+const float PI = 3.141592;
+
+// The direction of a texel, following the orientation of the cube map faces
+vec3 cube_dir(int face, vec2 st)
+{
+	vec2 c = st * 2.0 - 1.0;
+	if (face == 0) return vec3(1, -c.y, -c.x);
+	if (face == 1) return vec3(-1, -c.y, c.x);
+	if (face == 2) return vec3(c.x, 1, c.y);
+	if (face == 3) return vec3(c.x, -1, -c.y);
+	if (face == 4) return vec3(c.x, -c.y, 1);
+	return vec3(-c.x, -c.y, -1);
+}
+
+vec2 hammersley(int i, int n)
+{
+	uint bits = uint(i);
+	bits = (bits << 16u) | (bits >> 16u);
+	bits = ((bits & 0x55555555u) << 1u) | ((bits & 0xAAAAAAAAu) >> 1u);
+	bits = ((bits & 0x33333333u) << 2u) | ((bits & 0xCCCCCCCCu) >> 2u);
+	bits = ((bits & 0x0F0F0F0Fu) << 4u) | ((bits & 0xF0F0F0F0u) >> 4u);
+	bits = ((bits & 0x00FF00FFu) << 8u) | ((bits & 0xFF00FF00u) >> 8u);
+	return vec2(float(i) / float(n), float(bits) * 2.3283064365386963e-10);
+}
+
+vec3 importance_sample_ggx(vec2 xi, vec3 N, float roughness)
+{
+	float a = roughness * roughness;
+	float phi = 2.0 * PI * xi.x;
+	float cos_theta = sqrt((1.0 - xi.y) / (1.0 + (a * a - 1.0) * xi.y));
+	float sin_theta = sqrt(1.0 - cos_theta * cos_theta);
+	vec3 H = vec3(cos(phi) * sin_theta, sin(phi) * sin_theta, cos_theta);
+
+	vec3 up = abs(N.z) < 0.999 ? vec3(0, 0, 1) : vec3(1, 0, 0);
+	vec3 T = normalize(cross(up, N));
+	vec3 B = cross(N, T);
+	return normalize(T * H.x + B * H.y + N * H.z);
+}
+
+float distribution_ggx(float NdotH, float roughness)
+{
+	float a = roughness * roughness;
+	float a2 = a * a;
+	float d = NdotH * NdotH * (a2 - 1.0) + 1.0;
+	return a2 / (PI * d * d);
+}
+
+void main()
+{
+	vec2 st = gl_FragCoord.xy / u_target_size;
+	vec3 N = normalize(cube_dir(u_cube_face, st));
+
+	// A mirror doesn't need the convolution
+	if (u_prefilter_roughness == 0.0)
+	{
+		io_color = textureLod(u_tex0, N, 0.0).rgb;
+		return;
+	}
+
+	// Assumes the view direction equals to the normal
+	float source_size = float(textureSize(u_tex0, 0).x);
+	float texel_angle = 4.0 * PI / (6.0 * source_size * source_size);
+
+	vec3 sum = vec3(0);
+	float weight = 0;
+	for (int i = 0; i < SAMPLES; i++)
+	{
+		vec3 H = importance_sample_ggx(hammersley(i, SAMPLES), N, u_prefilter_roughness);
+		vec3 L = normalize(2.0 * dot(N, H) * H - N);
+		float NdotL = dot(N, L);
+		if (NdotL <= 0.0) continue;
+
+		// Reads a blurrier mipmap for a sample covering a wider angle, to suppress the noise
+		float NdotH = max(dot(N, H), 0.0);
+		float pdf = distribution_ggx(NdotH, u_prefilter_roughness) / 4.0 + 0.0001;
+		float sample_angle = 1.0 / (float(SAMPLES) * pdf + 0.0001);
+		float lod = max(0.5 * log2(sample_angle / texel_angle), 0.0);
+
+		sum += textureLod(u_tex0, L, lod).rgb * NdotL;
+		weight += NdotL;
+	}
+	io_color = sum / max(weight, 0.0001);
+}
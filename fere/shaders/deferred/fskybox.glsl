@@ -0,0 +1,23 @@
+#version 330 core
+
+// depth of the G-buffer
+uniform sampler2D u_tex0;
+uniform samplerCube u_tex1;
+
+uniform mat4 u_inv_camera_trans;
+uniform vec3 u_cpos;
+uniform float u_sky_intensity;
+
+layout (location = 0) out vec4 io_color;
+
+void main()
+{
+	ivec2 p = ivec2(gl_FragCoord.xy);
+	// Only where nothing has been drawn
+	if (texelFetch(u_tex0, p, 0).r < 1.0) discard;
+
+	vec2 uv = (vec2(p) + 0.5) / vec2(textureSize(u_tex0, 0));
+	vec4 far = u_inv_camera_trans * vec4(uv * 2.0 - 1.0, 1.0, 1.0);
+	vec3 dir = normalize(far.xyz / far.w - u_cpos);
+	io_color = vec4(texture(u_tex1, dir).rgb * u_sky_intensity, 1);
+}
@@ -0,0 +1,77 @@
+#version 330 core
+/*
+0(pos), 1(norm), 7(lighting)
+*/
+uniform sampler2D u_tex0;
+uniform sampler2D u_tex1;
+uniform isampler2D u_tex7;
+
+// projection * view of the camera
+uniform mat4 u_camera_trans;
+uniform vec3 u_cpos;
+uniform float u_ssao_strength;
+uniform float u_ssao_radius;
+
+layout (location = 0) out float io_occlusion;
+
+const int SAMPLES = 16;
+const float GOLDEN_ANGLE = 2.39996323;
+
+float hash(vec2 p)
+{
+	return fract(sin(dot(p, vec2(12.9898, 78.233))) * 43758.5453);
+}
+
+// i-th direction in the unit hemisphere around +z, denser near the center
+vec3 kernel(int i)
+{
+	float t = (float(i) + 0.5) / float(SAMPLES);
+	float z = sqrt(1.0 - t);
+	float r = sqrt(t);
+	float phi = GOLDEN_ANGLE * float(i);
+	float scale = mix(0.1, 1.0, t * t);
+	return vec3(r * cos(phi), r * sin(phi), z) * scale;
+}
+
+void main()
+{
+	ivec2 p = ivec2(gl_FragCoord.xy);
+	if (texelFetch(u_tex7, p, 0).r == 0)
+	{
+		io_occlusion = 1.0;
+		return;
+	}
+
+	vec3 P = texelFetch(u_tex0, p, 0).rgb;
+	vec3 N = normalize(texelFetch(u_tex1, p, 0).rgb);
+
+	// randomly rotated tangent space, to trade banding for noise which the blur removes
+	vec3 r = normalize(vec3(hash(gl_FragCoord.xy), hash(gl_FragCoord.xy + 17.0), hash(gl_FragCoord.xy + 31.0)) * 2.0 - 1.0);
+	vec3 T = r - N * dot(r, N);
+	T = length(T) < 0.0001 ? normalize(cross(N, vec3(0, 0, 1) + vec3(0.1, 0, 0))) : normalize(T);
+	vec3 B = cross(N, T);
+
+	ivec2 size = textureSize(u_tex0, 0);
+	float occlusion = 0;
+	for (int i = 0; i < SAMPLES; i++)
+	{
+		vec3 k = kernel(i);
+		vec3 S = P + (T * k.x + B * k.y + N * k.z) * u_ssao_radius;
+
+		vec4 c = u_camera_trans * vec4(S, 1);
+		if (c.w <= 0.0) continue;
+		vec2 uv = c.xy / c.w * 0.5 + 0.5;
+		if (uv.x < 0.0 || uv.x >= 1.0 || uv.y < 0.0 || uv.y >= 1.0) continue;
+
+		ivec2 q = ivec2(uv * vec2(size));
+		if (texelFetch(u_tex7, q, 0).r == 0) continue;
+		vec3 Q = texelFetch(u_tex0, q, 0).rgb;
+
+		// the surface seen there is in front of the sample, and close enough to occlude
+		float bias = 0.02 * u_ssao_radius;
+		float range = smoothstep(0.0, 1.0, u_ssao_radius / max(length(Q - P), 0.0001));
+		occlusion += (length(Q - u_cpos) < length(S - u_cpos) - bias ? 1.0 : 0.0) * range;
+	}
+
+	io_occlusion = clamp(1.0 - occlusion / float(SAMPLES) * u_ssao_strength, 0.0, 1.0);
+}
@@ -0,0 +1,23 @@
+#version 330 core
+
+// raw occlusion
+uniform sampler2D u_tex0;
+
+layout (location = 0) out float io_occlusion;
+
+void main()
+{
+	ivec2 p = ivec2(gl_FragCoord.xy);
+	ivec2 size = textureSize(u_tex0, 0);
+
+	float sum = 0;
+	for (int i = -2; i < 2; i++)
+	{
+		for (int j = -2; j < 2; j++)
+		{
+			ivec2 q = clamp(p + ivec2(i, j), ivec2(0), size - 1);
+			sum += texelFetch(u_tex0, q, 0).r;
+		}
+	}
+	io_occlusion = sum / 16.0;
+}
@@ -0,0 +1,102 @@
+#version 330 core
+/*
+0(pos), 1(norm), 3(roughness), 7(lighting)
+8(shaded scene), 9(depth)
+*/
+uniform sampler2D u_tex0;
+uniform sampler2D u_tex1;
+uniform sampler2D u_tex3;
+uniform isampler2D u_tex7;
+uniform sampler2D u_tex8;
+uniform sampler2D u_tex9;
+
+// projection * view of the camera, and its inverse
+uniform mat4 u_camera_trans;
+uniform mat4 u_inv_camera_trans;
+uniform vec3 u_cpos;
+uniform int u_ssr_max_steps;
+uniform float u_ssr_max_distance;
+uniform float u_ssr_thickness;
+
+// (reflected color, confidence)
+layout (location = 0) out vec4 io_reflection;
+
+// Rougher surfaces are left to the prefiltered reflections
+const float MAX_ROUGHNESS = 0.6;
+const int REFINE_STEPS = 5;
+// the width of the fade at the screen edges, in uv
+const float EDGE_FADE = 0.1;
+
+// Returns false if `S` is behind the camera or off the screen.
+bool project(vec3 S, out vec2 uv)
+{
+	vec4 c = u_camera_trans * vec4(S, 1);
+	if (c.w <= 0.0) return false;
+	uv = c.xy / c.w * 0.5 + 0.5;
+	return uv.x >= 0.0 && uv.x < 1.0 && uv.y >= 0.0 && uv.y < 1.0;
+}
+
+// The distance from the camera to the surface in the depth buffer at `uv`
+float surface_distance(vec2 uv)
+{
+	float depth = texture(u_tex9, uv).r;
+	if (depth >= 1.0) return 1e30;
+	vec4 w = u_inv_camera_trans * vec4(vec3(uv, depth) * 2.0 - 1.0, 1);
+	return length(w.xyz / w.w - u_cpos);
+}
+
+void main()
+{
+	ivec2 p = ivec2(gl_FragCoord.xy);
+	io_reflection = vec4(0);
+	if (texelFetch(u_tex7, p, 0).r != 1) return;
+
+	float ro = texelFetch(u_tex3, p, 0).r;
+	if (ro >= MAX_ROUGHNESS) return;
+
+	vec3 P = texelFetch(u_tex0, p, 0).rgb;
+	vec3 N = normalize(texelFetch(u_tex1, p, 0).rgb);
+	vec3 R = reflect(normalize(P - u_cpos), N);
+
+	float step_size = u_ssr_max_distance / float(max(u_ssr_max_steps, 1));
+	vec3 front = P;
+	vec2 uv;
+	bool hit = false;
+	float travel = 0.0;
+	for (int i = 1; i <= u_ssr_max_steps; i++)
+	{
+		vec3 S = P + R * step_size * float(i);
+		if (!project(S, uv)) break;
+		float gap = length(S - u_cpos) - surface_distance(uv);
+		if (gap > 0.0 && gap < u_ssr_thickness)
+		{
+			// Binary search between the last point in front of the surface and this
+			vec3 back = S;
+			for (int j = 0; j < REFINE_STEPS; j++)
+			{
+				vec3 M = (front + back) * 0.5;
+				project(M, uv);
+				if (length(M - u_cpos) > surface_distance(uv)) back = M;
+				else front = M;
+			}
+			project(back, uv);
+			travel = length(back - P);
+			hit = true;
+			break;
+		}
+		front = S;
+	}
+	if (!hit) return;
+
+	// The surface hit must face the ray
+	ivec2 q = ivec2(uv * vec2(textureSize(u_tex0, 0)));
+	if (texelFetch(u_tex7, q, 0).r == 0) return;
+	if (dot(normalize(texelFetch(u_tex1, q, 0).rgb), R) > 0.0) return;
+
+	vec2 edge = smoothstep(0.0, EDGE_FADE, uv) * smoothstep(0.0, EDGE_FADE, 1.0 - uv);
+	float confidence = edge.x * edge.y;
+	confidence *= 1.0 - smoothstep(0.5, 1.0, travel / u_ssr_max_distance);
+	confidence *= 1.0 - smoothstep(MAX_ROUGHNESS * 0.5, MAX_ROUGHNESS, ro);
+
+	io_reflection = vec4(texture(u_tex8, uv).rgb, confidence);
+}
@@ -0,0 +1,42 @@
+#version 330 core
+/*
+0(pos), 1(norm), 2(bc), 3(roughness), 4(metalness), 7(lighting)
+8(reflections), 9(BRDF lookup table), 10(ambient occlusion)
+*/
+uniform vec3 u_cpos;
+
+uniform sampler2D u_tex0;
+uniform sampler2D u_tex1;
+uniform sampler2D u_tex2;
+uniform sampler2D u_tex3;
+uniform sampler2D u_tex4;
+uniform isampler2D u_tex7;
+uniform sampler2D u_tex8;
+uniform sampler2D u_tex9;
+uniform sampler2D u_tex10;
+
+layout (location = 0) out vec4 io_color;
+
+void main()
+{
+	ivec2 p = ivec2(gl_FragCoord.xy);
+	if (texelFetch(u_tex7, p, 0).r != 1) discard;
+	vec4 reflection = texelFetch(u_tex8, p, 0);
+	if (reflection.a <= 0.0) discard;
+
+	vec3 wpos = texelFetch(u_tex0, p, 0).rgb;
+	vec3 N = normalize(texelFetch(u_tex1, p, 0).rgb);
+	vec3 bc = texelFetch(u_tex2, p, 0).rgb;
+	float ro = texelFetch(u_tex3, p, 0).r;
+	float mt = texelFetch(u_tex4, p, 0).r;
+	float ao = texelFetch(u_tex10, p, 0).r;
+
+	vec3 V = normalize(u_cpos - wpos);
+	float NdotV = max(dot(N, V), 0.0001);
+
+	vec3 F0 = mix(vec3(0.04), bc, mt);
+	vec2 brdf = texture(u_tex9, vec2(NdotV, ro)).rg;
+	vec3 specular = reflection.rgb * (F0 * brdf.x + brdf.y);
+
+	io_color = vec4(specular * reflection.a * ao, 1);
+}
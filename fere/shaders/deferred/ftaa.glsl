@@ -0,0 +1,51 @@
+#version 330 core
+
+// current frame, history, motion
+uniform sampler2D u_tex0;
+uniform sampler2D u_tex1;
+uniform sampler2D u_tex2;
+
+uniform bool u_history_valid;
+
+layout (location = 0) out vec3 io_color;
+
+// The weight of the current frame
+const float FEEDBACK = 0.1;
+
+float luma(vec3 c)
+{
+	return dot(c, vec3(0.299, 0.587, 0.114));
+}
+
+void main()
+{
+	ivec2 p = ivec2(gl_FragCoord.xy);
+	ivec2 size = textureSize(u_tex0, 0);
+	vec3 current = texelFetch(u_tex0, p, 0).rgb;
+
+	vec2 uv = (vec2(p) + 0.5) / vec2(size) - texelFetch(u_tex2, p, 0).rg;
+	if (!u_history_valid || uv.x < 0.0 || uv.y < 0.0 || uv.x > 1.0 || uv.y > 1.0)
+	{
+		io_color = current;
+		return;
+	}
+
+	// Clamp the history into the neighborhood, to reject the stale colors
+	vec3 lo = current;
+	vec3 hi = current;
+	for (int i = -1; i <= 1; i++)
+	{
+		for (int j = -1; j <= 1; j++)
+		{
+			vec3 c = texelFetch(u_tex0, clamp(p + ivec2(i, j), ivec2(0), size - 1), 0).rgb;
+			lo = min(lo, c);
+			hi = max(hi, c);
+		}
+	}
+	vec3 history = clamp(texture(u_tex1, uv).rgb, lo, hi);
+
+	// Weights by the inverse luminance, so that bright pixels don't flicker
+	float wc = FEEDBACK / (1.0 + luma(current));
+	float wh = (1.0 - FEEDBACK) / (1.0 + luma(history));
+	io_color = (current * wc + history * wh) / (wc + wh);
+}
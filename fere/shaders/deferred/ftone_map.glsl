@@ -0,0 +1,45 @@
+#version 330 core
+
+// HDR lighting
+uniform sampler2D u_tex0;
+// adapted luminance
+uniform sampler2D u_tex1;
+
+uniform int u_tone_map; // 0: clamp, 1: Reinhard, 2: ACES filmic
+uniform float u_exposure;
+uniform bool u_auto_exposure;
+uniform vec2 u_exposure_range;
+
+layout (location = 0) out vec4 io_color;
+
+// Narkowicz's fit of the ACES filmic curve
+vec3 aces_filmic(vec3 x)
+{
+	const float a = 2.51;
+	const float b = 0.03;
+	const float c = 2.43;
+	const float d = 0.59;
+	const float e = 0.14;
+	return clamp((x * (a * x + b)) / (x * (c * x + d) + e), 0.0, 1.0);
+}
+
+void main()
+{
+	ivec2 p = ivec2(gl_FragCoord.xy);
+	vec3 hdr = texelFetch(u_tex0, p, 0).rgb;
+
+	float exposure = u_exposure;
+	if (u_auto_exposure)
+	{
+		float lum = texelFetch(u_tex1, ivec2(0, 0), 0).r;
+		exposure = clamp(u_exposure / max(lum, 0.0001), u_exposure_range.x, u_exposure_range.y);
+	}
+	vec3 color = hdr * exposure;
+
+	if (u_tone_map == 1)
+		color = color / (color + vec3(1.0));
+	else if (u_tone_map == 2)
+		color = aces_filmic(color);
+
+	io_color = vec4(clamp(color, 0.0, 1.0), 1.0);
+}
@@ -0,0 +1,26 @@
+#version 330 core
+in vec3 io_pos;
+in vec3 io_norm;
+in vec2 io_tex;
+in vec3 io_fnorm;
+
+uniform mat4 u_projection;
+uniform mat4 u_view;
+uniform mat4 u_model;
+
+// the previous projection * view with the jitter, and the previous model of the object
+uniform mat4 u_prev_camera_trans;
+uniform mat4 u_prev_model;
+
+out vec4 current;
+out vec4 previous;
+
+void main()
+{
+    // The same as `vstandard.glsl`, so that it passes the depth test against the G-buffer
+    vec4 temp = u_model * vec4(io_pos, 1);
+    gl_Position = u_projection * u_view * temp;
+
+    current = gl_Position;
+    previous = u_prev_camera_trans * (u_prev_model * vec4(io_pos, 1));
+}
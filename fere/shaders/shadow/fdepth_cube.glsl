@@ -0,0 +1,12 @@
+#version 330 core
+in vec3 wpos;
+
+// position of the light
+uniform vec3 u_cpos;
+uniform float u_far;
+
+void main()
+{
+	// linear distance from the light, so that it can be compared in any direction
+	gl_FragDepth = length(wpos - u_cpos) / u_far;
+}
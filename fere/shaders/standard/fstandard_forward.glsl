@@ -0,0 +1,258 @@
+#version 330 core
+in vec3 wnormal;
+in vec3 wpos;
+in vec2 uv;
+
+struct SLight // could be either point or directional
+{
+	vec4 wpos;
+	vec3 color;
+	mat4 trans;
+	bool round;
+	float smoothness;
+	vec3 attenuation;
+	float radius;
+};
+/// [0, u_major_light_num) are major lights, [u_major_light_num, u_light_num) are sun, omni and point lights.
+/// Sun lights have `wpos.w` of 0, with the direction towards the sun in `wpos.xyz`.
+uniform SLight u_lights[16];
+uniform int u_light_num;
+uniform int u_major_light_num;
+uniform vec3 u_ambient;
+uniform vec3 u_cpos;
+
+// irradiance of the environment lights, already multiplied by their intensities
+uniform vec3 u_sh_coeff[18];
+// the prefiltered specular of the first environment light, or 0 for none
+uniform float u_ibl_intensity;
+uniform float u_ibl_levels;
+// the environment from the sky, or 0 for none
+uniform float u_sky_intensity;
+uniform float u_sky_lod;
+
+uniform vec3 u_basecolor;
+uniform float u_roughness;
+uniform float u_metalness;
+uniform float u_transparent;
+
+uniform bool u_basecolor_on;
+uniform bool u_roughness_on;
+uniform bool u_metalness_on;
+uniform bool u_transparent_on;
+
+/*
+texture binding
+0 : basecolor
+1 : roughness
+2 : metalness
+3 : prefiltered specular of the environment light
+4 : BRDF lookup table
+5 : normal
+6 : transparency
+7 : sky
+8(pos), 9(norm), 10(bc), 11(roughness), 12(metalness), 13(emission), 14(_), 15(lighting)
+*/
+uniform sampler2D u_tex0;
+uniform sampler2D u_tex1;
+uniform sampler2D u_tex2;
+uniform samplerCube u_tex3;
+uniform sampler2D u_tex4;
+uniform sampler2D u_tex5;
+uniform sampler2D u_tex6;
+uniform samplerCube u_tex7;
+
+uniform sampler2D u_tex8;
+uniform isampler2D u_tex15;
+
+layout (location = 0) out vec4 io_color;
+
+// dis : distance to the light | att : (constant, linear, quadratic) | radius : range of the light, 0 for infinite
+float attenuate(float dis, vec3 att, float radius)
+{
+	float a = 1.0 / max(att.x + att.y * dis + att.z * dis * dis, 0.0001);
+	if (radius > 0.0)
+	{
+		// fades to zero at the boundary of the light volume
+		float r = clamp(1.0 - pow(dis / radius, 4.0), 0.0, 1.0);
+		a *= r * r;
+	}
+	return a;
+}
+
+const float PI = 3.141592;
+
+vec3 spherical_harmonics(vec3 sh_coeff[18], vec3 p, mat3 table)
+{
+    vec3 result = vec3(0);
+
+    result += sh_coeff[0];
+
+    result += sh_coeff[1] * p[1];
+    result += sh_coeff[2] * p[2];
+    result += sh_coeff[3] * p[0];
+
+    result += sh_coeff[4] * table[0][1];
+    result += sh_coeff[5] * table[1][2];
+    result += sh_coeff[6] * (3 * table[2][2] - 1);
+    result += sh_coeff[7] * table[0][2];
+    result += sh_coeff[8] * (table[0][0] - table[1][1]);
+
+    result += sh_coeff[9] * table[0][1] * (table[0][0] - table[1][1]);
+    result += sh_coeff[10] * table[1][2] * (3 * table[0][0] - table[1][1]);
+    result += sh_coeff[11] * table[0][1] * (7 * table[2][2] - 1);
+    result += sh_coeff[12] * table[1][2] * (7 * table[2][2] - 3);
+    result += sh_coeff[13] * (table[2][2] * (35 * table[2][2] - 30) + 3);
+    result += sh_coeff[14] * table[0][2] * (7 * table[2][2] - 3);
+    result += sh_coeff[15] * (table[0][0] - table[1][1]) * (7 * table[2][2] - 1);
+    result += sh_coeff[16] * table[0][2] * (table[0][0] - 3 * table[1][1]);
+    result += sh_coeff[17] * (table[0][0] * (table[0][0] - 3 * table[1][1]) - table[1][1] * (3 * table[0][0] - table[1][1]));
+
+	return result;
+}
+
+// PBR formula
+vec3 fresnelSchlick(float cosTheta, vec3 F0)
+{
+    return F0 + (1.0 - F0) * pow(1.0 - cosTheta, 5.0);
+} 
+// Schlick with the roughness, for the light from every direction
+vec3 fresnel_schlick_roughness(float cos_theta, vec3 F0, float roughness)
+{
+	return F0 + (max(vec3(1.0 - roughness), F0) - F0) * pow(1.0 - cos_theta, 5.0);
+}
+float DistributionGGX(vec3 N, vec3 H, float roughness)
+{
+    float a      = roughness*roughness;
+    float a2     = a*a;
+    float NdotH  = max(dot(N, H), 0.0);
+    float NdotH2 = NdotH*NdotH;
+	
+    float num   = a2;
+    float denom = (NdotH2 * (a2 - 1.0) + 1.0);
+    denom = 3.141592 * denom * denom;
+    return num / denom;
+}
+float GeometrySchlickGGX(float NdotV, float roughness)
+{
+    float r = (roughness + 1.0);
+    float k = (r*r) / 8.0;
+
+    float num   = NdotV;
+    float denom = NdotV * (1.0 - k) + k;
+	
+    return num / denom;
+}
+float GeometrySmith(vec3 N, vec3 V, vec3 L, float roughness)
+{
+    float NdotV = max(dot(N, V), 0.0);
+    float NdotL = max(dot(N, L), 0.0);
+    float ggx2  = GeometrySchlickGGX(NdotV, roughness);
+    float ggx1  = GeometrySchlickGGX(NdotL, roughness);
+	
+    return ggx1 * ggx2;
+}
+
+// P : position of fragment | N : normal of fragment | P_c : camera position
+// P_l : light position | lc : light color | att, radius : attenuation of the light
+// bc : basecolor | ro : roughness | mt : metalness
+vec3 shade_pbr(vec3 P, vec3 N, vec3 P_c, vec4 P_l, vec3 lc, vec3 att, float radius, vec3 bc, float ro, float mt)
+{
+	vec3 V = normalize(P_c - P);
+	vec3 L = P_l.w == 0.0 ? normalize(P_l.xyz) : normalize(P_l.xyz - P);
+	vec3 H = normalize(V + L);
+
+	float dis = P_l.w == 0.0 ? 0.0 : length(P_l.xyz - P);
+	float NDL = dot(N, L);
+	float NDV = dot(N, V);
+
+	if(NDL <= 0.0 || NDV <= 0.0) return vec3(0);
+
+	float NDF = DistributionGGX(N, H, ro);
+	float G = GeometrySmith(N, V, L, ro);
+
+	vec3 F0 = vec3(0.04); 
+	F0 = mix(F0, bc, mt);
+	vec3 F = fresnelSchlick(NDL, F0);
+	
+	vec3 kS = F;
+    vec3 kD = vec3(1.0) - kS;
+    kD *= 1.0 - mt;
+
+	vec3 numerator    = NDF * G * F;
+	float denominator = 4.0 * max(dot(N, V), 0.0) * max(dot(N, L), 0.0);
+	vec3 specular     = numerator / max(denominator, 0.001);  
+    
+	float attenuation = attenuate(dis, att, radius);
+    vec3 radiance = lc * attenuation; 
+	return (kD * bc / 3.141592 + specular) * radiance * NDL;
+}
+
+// Whether the point lies in the frustum of the major light (no shadow test here)
+float shade_spot(int i, vec3 P)
+{
+	vec4 tpos = u_lights[i].trans * vec4(P, 1);
+	vec3 pc = tpos.xyz/tpos.w;
+
+	if (u_lights[i].round) 
+	{
+		float dis = pc.x*pc.x + pc.y*pc.y;
+		if (dis > 1)
+			return 0;
+		return 1 - pow(dis, 1 + u_lights[i].smoothness * 10); 
+	}
+	if(abs(pc.x) >= 1.0 || abs(pc.y) >= 1.0) 
+		return 0;
+	if(pc.z <= 0)
+		return 0;
+	return 1;
+}
+
+void main()
+{
+	// Manual depth test against the opaque G-buffer
+	ivec2 p = ivec2(gl_FragCoord.xy);
+	if (texelFetch(u_tex15, p, 0).r != 0)
+	{
+		vec3 opaque = texelFetch(u_tex8, p, 0).rgb;
+		if (length(opaque - u_cpos) < length(wpos - u_cpos) - 0.001) discard;
+	}
+
+	vec3 N = normalize(wnormal);
+
+	vec3 bc = u_basecolor_on ? u_basecolor : texture(u_tex0, uv).rgb;
+	float ro = u_roughness_on ? u_roughness : texture(u_tex1, uv).r;
+	float mt = u_metalness_on ? u_metalness : texture(u_tex2, uv).r;
+	float tr = u_transparent_on ? u_transparent : texture(u_tex6, uv).r;
+
+	vec3 res = bc * u_ambient;
+	for (int i = 0; i < u_light_num; i++)
+	{
+		float weight = i < u_major_light_num ? shade_spot(i, wpos) : 1.0;
+		if (weight < 0.0001) continue;
+		vec3 x = shade_pbr(wpos, N, u_cpos, u_lights[i].wpos, u_lights[i].color * weight,
+			u_lights[i].attenuation, u_lights[i].radius, bc, ro, mt);
+		res += x;
+	}
+
+	// Environment, which takes neither the screen-space reflections nor the reflection probes
+	vec3 V = normalize(u_cpos - wpos);
+	float NdotV = max(dot(N, V), 0.0001);
+	vec3 F0 = mix(vec3(0.04), bc, mt);
+	vec3 kD = (1.0 - fresnel_schlick_roughness(NdotV, F0, ro)) * (1.0 - mt);
+
+	mat3 table;
+	for (int i = 0; i < 3; i++)
+		for (int j = 0; j < 3; j++)
+			table[i][j] = N[i] * N[j];
+	res += kD * max(spherical_harmonics(u_sh_coeff, N, table), vec3(0)) * bc / PI;
+	if (u_ibl_intensity > 0.0)
+	{
+		vec3 prefiltered = textureLod(u_tex3, reflect(-V, N), ro * (u_ibl_levels - 1.0)).rgb;
+		vec2 brdf = texture(u_tex4, vec2(NdotV, ro)).rg;
+		res += prefiltered * (F0 * brdf.x + brdf.y) * u_ibl_intensity;
+	}
+	if (u_sky_intensity > 0.0)
+		res += bc * textureLod(u_tex7, N, u_sky_lod).rgb * u_sky_intensity;
+
+	io_color = vec4(res, 1.0 - tr);
+}
@@ -8,16 +8,288 @@ pub struct FereConfigs {
 
     pub shadow_resolution: usize,
     pub probe_resolution: usize,
+    /// The maximum number of major lights of a chamber in a frame, both spot and omni-directional.
     pub max_major_lights: usize,
 
+    /// The number of cascades of a sun light's shadow map.
+    pub sun_cascade_num: usize,
+    /// The resolution of each cascade.
+    pub sun_shadow_resolution: usize,
+
     pub video_record: bool,
 
     pub irradiance_volume: Option<IrradianceVolumeConfigs>,
 
+    /// Screen-space ambient occlusion. `None` to disable.
+    pub ssao: Option<SsaoConfigs>,
+
+    /// Screen-space reflections. `None` to disable.
+    pub ssr: Option<SsrConfigs>,
+
     pub max_chamber_num: usize,
 
-    pub pv_scale: f32,
+    pub point_light_shading: PointLightShading,
+
+    pub anti_aliasing: AntiAliasing,
+}
+
+/// The irradiance volume of each chamber, which can be overridden by `ChamberConfig::irradiance_volume`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct IrradianceVolumeConfigs {
+    /// The number of SH coefficients of a probe: 9 (up to the 2nd band) or 18 (with the 4th band).
+    pub sh_params: usize,
+
+    /// The gap between the walls of the chamber and the outermost probes.
+    pub wall_margin: f32,
+
+    /// The desired gap between probes along each axis. It's adjusted to fill the chamber evenly.
+    pub spacing: Vec3,
+
+    /// The near plane of the cameras rendering the probes.
+    pub near: f32,
+
+    /// The far plane of the cameras rendering the probes.
+    pub far: f32,
+
+    /// The number of probe faces to render for each chamber per frame, where a probe has 6 faces.
+    pub faces_per_frame: usize,
+
+    /// Stops updating probes in a frame after this much time in millisecond,
+    /// even before `faces_per_frame`. At least one face is rendered anyway. `None` for no limit.
+    ///
+    /// It's shared by all chambers, so it must be `None` in `ChamberConfig::irradiance_volume`.
+    /// Leave it `None` for reproducible results, as it depends on the timing.
+    pub time_budget_ms: Option<f32>,
+
+    /// Which probes are updated first, after those marked by `Fere::mark_probes_dirty()`.
+    pub priority: ProbeUpdatePriority,
+
+    /// How the probes start before they're updated.
+    ///
+    /// `None` starts from the neutral state, which gives no irradiance.
+    /// `Some` starts from random coefficients generated by the seed, which are the same on every run.
+    pub seed: Option<u64>,
+}
+
+impl Default for IrradianceVolumeConfigs {
+    fn default() -> Self {
+        Self {
+            sh_params: 18,
+            wall_margin: 4.0,
+            spacing: Vec3::new(24.0, 24.0, 24.0),
+            near: 0.2,
+            far: 200.0,
+            faces_per_frame: 1,
+            time_budget_ms: None,
+            priority: ProbeUpdatePriority::Linear,
+            seed: None,
+        }
+    }
+}
+
+impl IrradianceVolumeConfigs {
+    /// Checks whether it can build the probe volume of a chamber of `room_size`.
+    pub fn validate(&self, room_size: &Vec3) -> Result<(), String> {
+        if self.sh_params != 9 && self.sh_params != 18 {
+            return Err(format!(
+                "`sh_params` must be 9 or 18, not {}",
+                self.sh_params
+            ));
+        }
+        if !self.wall_margin.is_finite() || self.wall_margin < 0.0 {
+            return Err("`wall_margin` must not be negative".to_owned());
+        }
+        if (0..3).any(|i| room_size[i] - self.wall_margin * 2.0 <= 0.0) {
+            return Err(format!(
+                "`wall_margin` {} leaves no room in the chamber of {:?}",
+                self.wall_margin,
+                room_size.as_slice()
+            ));
+        }
+        if self.spacing.iter().any(|x| !x.is_finite() || *x <= 0.0) {
+            return Err("`spacing` must be positive".to_owned());
+        }
+        if !self.near.is_finite()
+            || !self.far.is_finite()
+            || self.near <= 0.0
+            || self.far <= self.near
+        {
+            return Err("`near` and `far` must satisfy 0 < near < far".to_owned());
+        }
+        if self.faces_per_frame == 0 {
+            return Err("`faces_per_frame` must be positive".to_owned());
+        }
+        if let Some(x) = self.time_budget_ms {
+            if x.is_nan() || x <= 0.0 {
+                return Err("`time_budget_ms` must be positive".to_owned());
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks whether it can override `FereConfigs::irradiance_volume` for a chamber of `room_size`.
+    pub fn validate_override(&self, room_size: &Vec3) -> Result<(), String> {
+        if self.time_budget_ms.is_some() {
+            return Err(
+                "`time_budget_ms` is shared by all chambers, so it can't be overridden".to_owned(),
+            );
+        }
+        self.validate(room_size)
+    }
+}
+
+/// The order of updating the probes of an irradiance volume.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub enum ProbeUpdatePriority {
+    /// Walks the whole grid in order.
+    Linear,
+    /// Prefers the probes near the camera, while the far ones still get their turns as they get stale.
+    CameraDistance,
 }
 
 #[derive(Clone, Debug, Deserialize)]
-pub struct IrradianceVolumeConfigs {}
+pub struct SsaoConfigs {
+    /// How dark the occluded area gets. Use [0, 1].
+    pub strength: f32,
+
+    /// The radius of the hemisphere to find occluders, in world space.
+    pub radius: f32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SsrConfigs {
+    /// The number of steps to march a ray. More steps find thinner gaps but cost more.
+    pub max_steps: usize,
+
+    /// How far a ray goes, in world space.
+    pub max_distance: f32,
+
+    /// How thick a surface in the depth buffer is assumed to be, in world space.
+    pub thickness: f32,
+}
+
+/// How point lights are shaded.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub enum PointLightShading {
+    /// Draws a light volume for each light. Good for a few lights.
+    LightVolume,
+    /// Bins the lights into screen-space tiles and shades them in a single full-screen pass.
+    /// Good for hundreds or thousands of small lights.
+    Tiled,
+}
+
+/// How the final image is anti-aliased.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub enum AntiAliasing {
+    None,
+    /// A post-process pass finding and smoothing the edges. Cheap, but blurs a bit.
+    Fxaa,
+    /// Jitters the camera every frame and accumulates the history reprojected by the motion vectors.
+    /// Also handles the shimmering of thin geometries, but may ghost on fast motions.
+    Taa,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn room() -> Vec3 {
+        Vec3::new(40.0, 30.0, 20.0)
+    }
+
+    #[test]
+    fn validate_accepts_default() {
+        assert!(IrradianceVolumeConfigs::default().validate(&room()).is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_bad_sh_params() {
+        let configs = IrradianceVolumeConfigs {
+            sh_params: 16,
+            ..Default::default()
+        };
+        assert!(configs.validate(&room()).is_err());
+    }
+
+    #[test]
+    fn validate_rejects_margin_larger_than_room() {
+        for wall_margin in [-1.0, f32::NAN, 10.0, 20.0].iter() {
+            let configs = IrradianceVolumeConfigs {
+                wall_margin: *wall_margin,
+                ..Default::default()
+            };
+            assert!(configs.validate(&room()).is_err(), "{}", wall_margin);
+        }
+        let configs = IrradianceVolumeConfigs {
+            wall_margin: 9.9,
+            ..Default::default()
+        };
+        assert!(configs.validate(&room()).is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_non_positive_spacing() {
+        for spacing in [
+            Vec3::new(0.0, 24.0, 24.0),
+            Vec3::new(24.0, -1.0, 24.0),
+            Vec3::new(24.0, 24.0, f32::INFINITY),
+        ]
+        .iter()
+        {
+            let configs = IrradianceVolumeConfigs {
+                spacing: *spacing,
+                ..Default::default()
+            };
+            assert!(configs.validate(&room()).is_err(), "{:?}", spacing);
+        }
+    }
+
+    #[test]
+    fn validate_rejects_bad_near_far() {
+        for (near, far) in [(0.0, 10.0), (-0.1, 10.0), (10.0, 10.0), (10.0, 1.0)].iter() {
+            let configs = IrradianceVolumeConfigs {
+                near: *near,
+                far: *far,
+                ..Default::default()
+            };
+            assert!(configs.validate(&room()).is_err(), "{} {}", near, far);
+        }
+    }
+
+    #[test]
+    fn validate_rejects_zero_faces_per_frame() {
+        let configs = IrradianceVolumeConfigs {
+            faces_per_frame: 0,
+            ..Default::default()
+        };
+        assert!(configs.validate(&room()).is_err());
+    }
+
+    #[test]
+    fn validate_rejects_non_positive_budget() {
+        for budget in [0.0, -1.0, f32::NAN].iter() {
+            let configs = IrradianceVolumeConfigs {
+                time_budget_ms: Some(*budget),
+                ..Default::default()
+            };
+            assert!(configs.validate(&room()).is_err(), "{}", budget);
+        }
+        let configs = IrradianceVolumeConfigs {
+            time_budget_ms: Some(2.0),
+            ..Default::default()
+        };
+        assert!(configs.validate(&room()).is_ok());
+    }
+
+    #[test]
+    fn validate_override_rejects_budget() {
+        let configs = IrradianceVolumeConfigs {
+            time_budget_ms: Some(2.0),
+            ..Default::default()
+        };
+        assert!(configs.validate_override(&room()).is_err());
+        assert!(IrradianceVolumeConfigs::default()
+            .validate_override(&room())
+            .is_ok());
+    }
+}
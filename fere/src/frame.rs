@@ -12,6 +12,98 @@ pub struct FrameConfig {
 
     /// For a debug purpose, show the outlines of light volumes.
     pub show_lightvolume_outline: bool,
+
+    /// How the HDR lighting is mapped to the screen.
+    pub tone_mapping: ToneMapping,
+
+    /// Makes bright surfaces glow. `None` to disable.
+    pub bloom: Option<Bloom>,
+
+    /// Drives the animated emission of the `EmissiveStatic` surfaces.
+    pub clock: FrameClock,
+}
+
+/// The global frame clock.
+///
+/// The timepoints of every `EmissiveStatic` surface, including those seen by the irradiance probes,
+/// are played together by this.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrameClock {
+    /// The current frame, counted by the user.
+    pub frame: u64,
+
+    /// The number of frames for the whole cycle of the timepoints. `0` to stay at the first one.
+    pub cycle: u64,
+}
+
+impl FrameClock {
+    /// The position in the cycle, in [0, 1).
+    pub fn phase(&self) -> f64 {
+        if self.cycle == 0 {
+            0.0
+        } else {
+            (self.frame % self.cycle) as f64 / self.cycle as f64
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+pub struct Bloom {
+    /// The luminance above which the color spreads out.
+    pub threshold: f32,
+
+    /// The length of the downsample chain, which decides how wide the glow spreads.
+    /// It's capped by `MAX_BLOOM_LEVELS`.
+    pub levels: usize,
+
+    /// The weight of the glow added to the lighting.
+    pub intensity: f32,
+}
+
+/// Maps the HDR lighting into the displayable range.
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+pub struct ToneMapping {
+    pub operator: ToneMapOperator,
+    pub exposure: Exposure,
+}
+
+impl Default for ToneMapping {
+    fn default() -> Self {
+        Self {
+            operator: ToneMapOperator::Reinhard,
+            exposure: Exposure::Manual(1.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+pub enum ToneMapOperator {
+    /// Just clamps the color, so highlights clip.
+    Clamp,
+    Reinhard,
+    /// The filmic curve fitted to ACES.
+    AcesFilmic,
+}
+
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+pub enum Exposure {
+    /// The color is multiplied by this before the tone mapping.
+    Manual(f32),
+
+    /// The exposure adapts to the average luminance of the screen.
+    Auto {
+        /// The luminance that the average of the screen is mapped to. (e.g. 0.18)
+        key: f32,
+
+        /// How fast the exposure follows the screen, as the ratio per frame in (0, 1].
+        adaptation_rate: f32,
+
+        /// The lower bound of the exposure.
+        min: f32,
+
+        /// The upper bound of the exposure.
+        max: f32,
+    },
 }
 
 pub struct Frame {
@@ -1,2 +1,3 @@
 #[allow(dead_code)]
 pub mod probe_volume;
+pub mod reflection_probe;
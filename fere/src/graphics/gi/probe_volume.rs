@@ -1,8 +1,11 @@
 #![allow(clippy::needless_range_loop)]
 
-mod calculation;
+pub(crate) mod calculation;
+mod file;
+mod scheduler;
 mod suite;
 
+use crate::configs::IrradianceVolumeConfigs;
 use crate::SetCamera;
 use fere_common::*;
 use fere_common::{
@@ -11,6 +14,7 @@ use fere_common::{
 };
 use rand::{rngs::StdRng, Rng};
 
+pub use scheduler::ProbeScheduler;
 pub use suite::ProbeVolumeSuite;
 
 pub struct Probe {
@@ -25,8 +29,13 @@ pub struct ProbeVolume {
     sh_cache: [Vec<Vec<f32>>; 6],
     /// number of paramters per probe
     param: usize,
+    /// the desired gap between probes along each axis
+    spacing: Vec3,
     room_size: Vec3,
     min_gap: f32,
+    /// near and far of the probe cameras
+    near: f32,
+    far: f32,
 
     // 'Texture' means calculated final sh coefficients that will be fed to the shader
     /// number of texel
@@ -51,6 +60,18 @@ impl ProbeVolume {
         self.fb_size
     }
 
+    pub fn spacing(&self) -> Vec3 {
+        self.spacing
+    }
+
+    pub fn near(&self) -> f32 {
+        self.near
+    }
+
+    pub fn far(&self) -> f32 {
+        self.far
+    }
+
     pub fn room_size(&self) -> Vec3 {
         self.room_size
     }
@@ -69,8 +90,8 @@ impl ProbeVolume {
             dir.1,
             90.0_f32.to_radians(),
             1.0,
-            0.2,
-            200.0,
+            self.near,
+            self.far,
         )
     }
 
@@ -106,19 +127,20 @@ impl ProbeVolume {
         &self.texture_depth
     }
 
-    pub fn new(room_size: Vec3, scale: f32, fb_size: usize) -> Self {
-        let mut rng: StdRng = rand::SeedableRng::from_entropy();
+    /// `configs` must be validated for `room_size`.
+    pub fn new(room_size: Vec3, configs: &IrradianceVolumeConfigs, fb_size: usize) -> Self {
+        let mut rng: Option<StdRng> = configs.seed.map(rand::SeedableRng::seed_from_u64);
 
-        let param = 18;
+        let param = configs.sh_params;
         let sh_cache = calculation::calculate_sh_cache(fb_size, param);
-        let min_gap = 4.0;
+        let min_gap = configs.wall_margin;
         let room_size_shrinked = room_size - Vec3::from_element(min_gap) * 2.0;
 
         let mut number = IVec3::from_element(0);
         let mut cell_size = Vec3::from_element(0.0);
 
         for i in 0..3 {
-            number[i] = (room_size_shrinked[i] / scale).round() as i32;
+            number[i] = ((room_size_shrinked[i] / configs.spacing[i]).round() as i32).max(1);
             cell_size[i] = room_size_shrinked[i] / number[i] as f32;
             number[i] += 1;
         }
@@ -132,20 +154,22 @@ impl ProbeVolume {
 
             let pos = i_f.component_mul(&cell_size) + Vec3::from_element(min_gap)
                 - Vec3::new(room_size.x, room_size.y, 0.0) * 0.5;
-            for c in 0..param {
-                sh[c] = (
-                    Vec3::new(
-                        rng.gen_range(-1.0..1.0),
-                        rng.gen_range(-1.0..1.0),
-                        rng.gen_range(-1.0..1.0),
-                    ),
-                    Vec3::new(
-                        rng.gen_range(-1.0..1.0),
-                        rng.gen_range(-1.0..1.0),
-                        rng.gen_range(-1.0..1.0),
-                    ),
-                    rng.gen_range(0.0..1.0),
-                );
+            if let Some(rng) = rng.as_mut() {
+                for c in 0..param {
+                    sh[c] = (
+                        Vec3::new(
+                            rng.gen_range(-1.0..1.0),
+                            rng.gen_range(-1.0..1.0),
+                            rng.gen_range(-1.0..1.0),
+                        ),
+                        Vec3::new(
+                            rng.gen_range(-1.0..1.0),
+                            rng.gen_range(-1.0..1.0),
+                            rng.gen_range(-1.0..1.0),
+                        ),
+                        rng.gen_range(0.0..1.0),
+                    );
+                }
             }
             let probe = Probe { pos, sh };
             probes[ga.get(&i)] = Some(probe);
@@ -162,12 +186,15 @@ impl ProbeVolume {
         let texture_illumination = vec![Vec3::from_element(0.0); tsize];
         let texture_depth = vec![0.0; tsize];
 
-        ProbeVolume {
+        let mut probe_volume = ProbeVolume {
             fb_size,
             sh_cache,
             param,
+            spacing: configs.spacing,
             room_size,
             min_gap,
+            near: configs.near,
+            far: configs.far,
             texture_size,
             texture_diffuse,
             texture_illumination,
@@ -176,7 +203,10 @@ impl ProbeVolume {
             cell_size,
             number,
             offset,
-        }
+        };
+        // So that the textures show the initial state before the first update
+        probe_volume.update_texture();
+        probe_volume
     }
 
     pub fn update_probe(
@@ -5,6 +5,102 @@ use fere_common::{
     *,
 };
 
+/// (A_l, c_lm) of each coefficient; the convolution with the cosine lobe for its band,
+/// and the normalization of its basis.
+#[allow(clippy::approx_constant)]
+const SH_CONSTANTS: [(f32, f32); 18] = [
+    (3.141593, 0.282095),
+    (2.094395, 0.488603),
+    (2.094395, 0.488603),
+    (2.094395, 0.488603),
+    (0.785298, 1.092548),
+    (0.785298, 1.092548),
+    (0.785298, 0.315392),
+    (0.785298, 1.092548),
+    (0.785298, 0.546274),
+    (-0.130900, 2.503342),
+    (-0.130900, 1.77013),
+    (-0.130900, 0.946174),
+    (-0.130900, 0.669046),
+    (-0.130900, 0.105785),
+    (-0.130900, 0.669046),
+    (-0.130900, 0.473087),
+    (-0.130900, 1.77013),
+    (-0.130900, 0.625835),
+];
+
+/// The (unnormalized) SH basis at the direction `p`, as `spherical_harmonics()` of the shaders evaluates.
+fn sh_basis(p: &Vec3) -> [f32; 18] {
+    let mut table = Mat3::from_element(0.0);
+    for m in 0..3 {
+        for n in 0..3 {
+            *table.get_mut((m, n)).unwrap() = p[m] * p[n];
+        }
+    }
+    [
+        // l = 0
+        1.0,
+        // l = 1
+        p[1],
+        p[2],
+        p[0],
+        // l = 2
+        table[(0, 1)],
+        table[(1, 2)],
+        3.0 * table[(2, 2)] - 1.0,
+        table[(0, 2)],
+        table[(0, 0)] - table[(1, 1)],
+        // l = 4
+        table[(0, 1)] * (table[(0, 0)] - table[(1, 1)]),
+        table[(1, 2)] * (3.0 * table[(0, 0)] - table[(1, 1)]),
+        table[(0, 1)] * (7.0 * table[(2, 2)] - 1.0),
+        table[(1, 2)] * (7.0 * table[(2, 2)] - 3.0),
+        table[(2, 2)] * (35.0 * table[(2, 2)] - 30.0) + 3.0,
+        table[(0, 2)] * (7.0 * table[(2, 2)] - 3.0),
+        (table[(0, 0)] - table[(1, 1)]) * (7.0 * table[(2, 2)] - 1.0),
+        table[(0, 2)] * (table[(0, 0)] - 3.0 * table[(1, 1)]),
+        table[(0, 0)] * (table[(0, 0)] - 3.0 * table[(1, 1)])
+            - table[(1, 1)] * (3.0 * table[(0, 0)] - table[(1, 1)]),
+    ]
+}
+
+/// Projects a cube map onto the SH basis as the irradiance,
+/// so that `spherical_harmonics()` of the shaders gives the irradiance at a normal.
+///
+/// `faces` are the RGB texels of `size` * `size`, in the order and the orientation of `GL_TEXTURE_CUBE_MAP_POSITIVE_X + i`.
+pub fn project_cube_irradiance(faces: &[Vec<f32>], size: usize) -> Vec<Vec3> {
+    let mut result = vec![Vec3::zeros(); SH_CONSTANTS.len()];
+    for (i, face) in faces.iter().enumerate() {
+        for y in 0..size {
+            for x in 0..size {
+                let s = (x as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+                let t = (y as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+                let dir = match i {
+                    0 => Vec3::new(1.0, -t, -s),
+                    1 => Vec3::new(-1.0, -t, s),
+                    2 => Vec3::new(s, 1.0, t),
+                    3 => Vec3::new(s, -1.0, -t),
+                    4 => Vec3::new(s, -t, 1.0),
+                    _ => Vec3::new(-s, -t, -1.0),
+                };
+                // The solid angle of the texel
+                let weight = 4.0 / (size * size) as f32 / (1.0 + s * s + t * t).powf(1.5);
+
+                let k = (y * size + x) * 3;
+                let color = Vec3::new(face[k], face[k + 1], face[k + 2]);
+                let basis = sh_basis(&normalize(&dir));
+                for (c, value) in result.iter_mut().enumerate() {
+                    *value += color * (basis[c] * weight);
+                }
+            }
+        }
+    }
+    for (value, (a, norm)) in result.iter_mut().zip(SH_CONSTANTS.iter()) {
+        *value *= a * norm * norm;
+    }
+    result
+}
+
 pub fn calculate_sh_cache(fb_size: usize, param: usize) -> [Vec<Vec<f32>>; 6] {
     let mut result: [Vec<Vec<f32>>; 6] = Default::default();
     for i in 0..6 {
@@ -24,36 +120,10 @@ pub fn calculate_sh_cache(fb_size: usize, param: usize) -> [Vec<Vec<f32>>; 6] {
                     1.0,
                 );
                 let p = normalize(&(six_sides(i as SixDir) * q).xyz());
-                let mut table = Mat3::from_element(0.0);
-                for m in 0..3 {
-                    for n in 0..3 {
-                        *table.get_mut((m, n)).unwrap() = p[m] * p[n];
-                    }
+                let basis = sh_basis(&p);
+                for (c, v) in coeff.iter_mut().enumerate() {
+                    v[count] = basis[c];
                 }
-
-                // l = 0
-                coeff[0][count] = 1.0;
-                // l = 1
-                coeff[1][count] = p[1];
-                coeff[2][count] = p[2];
-                coeff[3][count] = p[0];
-                // l = 2
-                coeff[4][count] = table[(0, 1)];
-                coeff[5][count] = table[(1, 2)];
-                coeff[6][count] = 3.0 * table[(2, 2)] - 1.0;
-                coeff[7][count] = table[(0, 2)];
-                coeff[8][count] = table[(0, 0)] - table[(1, 1)];
-                // l = 4
-                coeff[9][count] = table[(0, 1)] * (table[(0, 0)] - table[(1, 1)]);
-                coeff[10][count] = table[(1, 2)] * (3.0 * table[(0, 0)] - table[(1, 1)]);
-                coeff[11][count] = table[(0, 1)] * (7.0 * table[(2, 2)] - 1.0);
-                coeff[12][count] = table[(1, 2)] * (7.0 * table[(2, 2)] - 3.0);
-                coeff[13][count] = table[(2, 2)] * (35.0 * table[(2, 2)] - 30.0) + 3.0;
-                coeff[14][count] = table[(0, 2)] * (7.0 * table[(2, 2)] - 3.0);
-                coeff[15][count] = (table[(0, 0)] - table[(1, 1)]) * (7.0 * table[(2, 2)] - 1.0);
-                coeff[16][count] = table[(0, 2)] * (table[(0, 0)] - 3.0 * table[(1, 1)]);
-                coeff[17][count] = table[(0, 0)] * (table[(0, 0)] - 3.0 * table[(1, 1)])
-                    - table[(1, 1)] * (3.0 * table[(0, 0)] - table[(1, 1)]);
                 /*
                 for h in 1..18 {
                     coeff[h][count] = 0.0;
@@ -91,7 +161,6 @@ impl Accessor for AccDepth {
 
 /// TODO: replace various subscribtion [] to unchecked get
 /// TODO: Parallel execution
-#[allow(clippy::approx_constant)]
 fn update_sub<T: Accessor>(
     probe: &mut Probe,
     dc: &[f32; 4],
@@ -144,50 +213,9 @@ fn update_sub<T: Accessor>(
     let k = (size * size * 6) as f64 / (2.0 * std::f64::consts::PI.powi(2)) * 20.0;
     let k = k as f32;
     for d in 0..dim {
-        let sh = &mut probe.sh;
-        let mut c;
-        let mut a;
-
-        a = 3.141593;
-        c = 0.282095;
-        *T::acc(&mut sh[0], d) *= c * a / k;
-
-        a = 2.094395;
-        c = 0.488603;
-        *T::acc(&mut sh[1], d) *= c * a / k;
-        *T::acc(&mut sh[2], d) *= c * a / k;
-        *T::acc(&mut sh[3], d) *= c * a / k;
-
-        a = 0.785298;
-        c = 1.092548;
-        *T::acc(&mut sh[4], d) *= c * a / k;
-        *T::acc(&mut sh[5], d) *= c * a / k;
-        c = 0.315392;
-        *T::acc(&mut sh[6], d) *= c * a / k;
-        c = 1.092548;
-        *T::acc(&mut sh[7], d) *= c * a / k;
-        c = 0.546274;
-        *T::acc(&mut sh[8], d) *= c * a / k;
-
-        a = -0.130900;
-        c = 2.503342;
-        *T::acc(&mut sh[9], d) *= c * a / k;
-        c = 1.77013;
-        *T::acc(&mut sh[10], d) *= c * a / k;
-        c = 0.946174;
-        *T::acc(&mut sh[11], d) *= c * a / k;
-        c = 0.669046;
-        *T::acc(&mut sh[12], d) *= c * a / k;
-        c = 0.105785;
-        *T::acc(&mut sh[13], d) *= c * a / k;
-        c = 0.669046;
-        *T::acc(&mut sh[14], d) *= c * a / k;
-        c = 0.473087;
-        *T::acc(&mut sh[15], d) *= c * a / k;
-        c = 1.77013;
-        *T::acc(&mut sh[16], d) *= c * a / k;
-        c = 0.625835;
-        *T::acc(&mut sh[17], d) *= c * a / k;
+        for (c, (a, norm)) in SH_CONSTANTS.iter().take(param).enumerate() {
+            *T::acc(&mut probe.sh[c], d) *= norm * a / k;
+        }
     }
 }
 
@@ -206,8 +234,8 @@ pub fn update_probe(
         probe.sh[p] = (Vec3::from_element(0.0), Vec3::from_element(0.0), 0.0);
     }
 
-    // TODO: sync this
-    let iproj = Mat4::new_perspective(90.0_f32.to_radians(), 1.0, 0.5, 200.0);
+    // Only the depth terms are used, which depend on the near and far of `ProbeVolume::camera()`
+    let iproj = Mat4::new_perspective(90.0_f32.to_radians(), 1.0, volume.near, volume.far);
     //for simplicity, we assume x = 0 and y = 0 for the original coord (afterh view trans)
     let dc = [iproj[(2, 2)], iproj[(3, 2)], iproj[(2, 3)], iproj[(3, 3)]];
 
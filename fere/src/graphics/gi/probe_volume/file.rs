@@ -0,0 +1,282 @@
+//! The binary file of a baked `ProbeVolume`.
+//!
+//! All values are little-endian, in the order of
+//! - the magic `FEREPV` and the version (u32)
+//! - fb_size (u32), params (u32), min_gap (f32), near (f32), far (f32)
+//! - spacing, room_size, cell_size, offset (f32 x 3 for each)
+//! - number, texture_size (i32 x 3 for each)
+//! - for each probe: pos (f32 x 3), then (diffuse (f32 x 3), illumination (f32 x 3), depth (f32)) for each param
+//!
+//! The textures are not stored, but filled from the probes when it's read.
+
+use super::*;
+use std::io::{self, Read, Write};
+
+const MAGIC: &[u8; 6] = b"FEREPV";
+const VERSION: u32 = 2;
+/// Upper bounds of the layout, so that a corrupted header can't request a huge allocation
+const MAX_NUMBER: i32 = 256;
+const MAX_FB_SIZE: usize = 1024;
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn write_u32(w: &mut impl Write, x: u32) -> io::Result<()> {
+    w.write_all(&x.to_le_bytes())
+}
+
+fn write_f32(w: &mut impl Write, x: f32) -> io::Result<()> {
+    w.write_all(&x.to_le_bytes())
+}
+
+fn write_vec3(w: &mut impl Write, x: &Vec3) -> io::Result<()> {
+    for i in 0..3 {
+        write_f32(w, x[i])?;
+    }
+    Ok(())
+}
+
+fn write_ivec3(w: &mut impl Write, x: &IVec3) -> io::Result<()> {
+    for i in 0..3 {
+        w.write_all(&x[i].to_le_bytes())?;
+    }
+    Ok(())
+}
+
+fn read_u32(r: &mut impl Read) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_f32(r: &mut impl Read) -> io::Result<f32> {
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf)?;
+    Ok(f32::from_le_bytes(buf))
+}
+
+fn read_vec3(r: &mut impl Read) -> io::Result<Vec3> {
+    Ok(Vec3::new(read_f32(r)?, read_f32(r)?, read_f32(r)?))
+}
+
+fn read_ivec3(r: &mut impl Read) -> io::Result<IVec3> {
+    let mut x = IVec3::zeros();
+    for i in 0..3 {
+        x[i] = read_u32(r)? as i32;
+    }
+    Ok(x)
+}
+
+impl ProbeVolume {
+    pub fn write(&self, w: &mut impl Write) -> io::Result<()> {
+        w.write_all(MAGIC)?;
+        write_u32(w, VERSION)?;
+
+        write_u32(w, self.fb_size as u32)?;
+        write_u32(w, self.param as u32)?;
+        write_f32(w, self.min_gap)?;
+        write_f32(w, self.near)?;
+        write_f32(w, self.far)?;
+        write_vec3(w, &self.spacing)?;
+        write_vec3(w, &self.room_size)?;
+        write_vec3(w, &self.cell_size)?;
+        write_vec3(w, &self.offset)?;
+        write_ivec3(w, &self.number)?;
+        write_ivec3(w, &self.texture_size)?;
+
+        for probe in &self.probes {
+            write_vec3(w, &probe.pos)?;
+            for (diffuse, illumination, depth) in &probe.sh {
+                write_vec3(w, diffuse)?;
+                write_vec3(w, illumination)?;
+                write_f32(w, *depth)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read(r: &mut impl Read) -> io::Result<Self> {
+        let mut magic = [0; 6];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("Not a probe volume file".to_owned()));
+        }
+        let version = read_u32(r)?;
+        if version != VERSION {
+            return Err(invalid(format!(
+                "Unsupported probe volume version {} (expected {})",
+                version, VERSION
+            )));
+        }
+
+        let fb_size = read_u32(r)? as usize;
+        let param = read_u32(r)? as usize;
+        let min_gap = read_f32(r)?;
+        let near = read_f32(r)?;
+        let far = read_f32(r)?;
+        let spacing = read_vec3(r)?;
+        let room_size = read_vec3(r)?;
+        let cell_size = read_vec3(r)?;
+        let offset = read_vec3(r)?;
+        let number = read_ivec3(r)?;
+        let texture_size = read_ivec3(r)?;
+
+        if fb_size == 0
+            || fb_size > MAX_FB_SIZE
+            || (param != 9 && param != 18)
+            || number.iter().any(|x| *x <= 0 || *x > MAX_NUMBER)
+        {
+            return Err(invalid("Invalid probe volume layout".to_owned()));
+        }
+        if texture_size != IVec3::new(number.x + 2, number.y + 2, (number.z + 2) * param as i32) {
+            return Err(invalid("Mismatched probe volume texture size".to_owned()));
+        }
+
+        // pos, then (diffuse, illumination, depth) for each param
+        let probe_bytes = (3 + param * 7) * 4;
+        let ga = GridAccessor3(number);
+        let body_size = ga
+            .size()
+            .checked_mul(probe_bytes)
+            .ok_or_else(|| invalid("Invalid probe volume layout".to_owned()))?;
+        // `take` grows the buffer only as far as the stream actually goes
+        let mut body = Vec::new();
+        r.take(body_size as u64).read_to_end(&mut body)?;
+        if body.len() != body_size {
+            return Err(invalid(format!(
+                "Truncated probe volume ({} of {} bytes of probes)",
+                body.len(),
+                body_size
+            )));
+        }
+
+        let r = &mut body.as_slice();
+        let mut probes = Vec::with_capacity(ga.size());
+        for _ in 0..ga.size() {
+            let pos = read_vec3(r)?;
+            let mut sh = Vec::with_capacity(param);
+            for _ in 0..param {
+                sh.push((read_vec3(r)?, read_vec3(r)?, read_f32(r)?));
+            }
+            probes.push(Probe { pos, sh });
+        }
+
+        let tsize = (texture_size.x * texture_size.y * texture_size.z) as usize;
+        let mut probe_volume = ProbeVolume {
+            fb_size,
+            sh_cache: calculation::calculate_sh_cache(fb_size, param),
+            param,
+            spacing,
+            room_size,
+            min_gap,
+            near,
+            far,
+            texture_size,
+            texture_diffuse: vec![Vec3::from_element(0.0); tsize],
+            texture_illumination: vec![Vec3::from_element(0.0); tsize],
+            texture_depth: vec![0.0; tsize],
+            probes,
+            cell_size,
+            number,
+            offset,
+        };
+        probe_volume.update_texture();
+        Ok(probe_volume)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> ProbeVolume {
+        let configs = IrradianceVolumeConfigs {
+            sh_params: 9,
+            spacing: Vec3::new(6.0, 6.0, 6.0),
+            seed: Some(7),
+            ..Default::default()
+        };
+        ProbeVolume::new(Vec3::new(20.0, 16.0, 12.0), &configs, 4)
+    }
+
+    fn serialize(probe_volume: &ProbeVolume) -> Vec<u8> {
+        let mut buf = Vec::new();
+        probe_volume.write(&mut buf).unwrap();
+        buf
+    }
+
+    fn read_error(buf: &[u8]) -> io::Error {
+        match ProbeVolume::read(&mut &buf[..]) {
+            Ok(_) => panic!("Read an invalid probe volume"),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let original = sample();
+        let read = ProbeVolume::read(&mut serialize(&original).as_slice()).unwrap();
+
+        assert_eq!(read.fb_size(), original.fb_size());
+        assert_eq!(read.params(), original.params());
+        assert_eq!(read.spacing(), original.spacing());
+        assert_eq!(read.room_size(), original.room_size());
+        assert_eq!(read.min_gap(), original.min_gap());
+        assert_eq!(read.near(), original.near());
+        assert_eq!(read.far(), original.far());
+        assert_eq!(read.cell_size(), original.cell_size());
+        assert_eq!(read.number(), original.number());
+        assert_eq!(read.offset(), original.offset());
+        assert_eq!(read.texture_size(), original.texture_size());
+        assert_eq!(read.probes().len(), original.probes().len());
+        for (a, b) in read.probes().iter().zip(original.probes()) {
+            assert_eq!(a.pos, b.pos);
+            assert_eq!(a.sh, b.sh);
+        }
+        assert_eq!(read.texture_diffuse(), original.texture_diffuse());
+        assert_eq!(read.texture_illumination(), original.texture_illumination());
+        assert_eq!(read.texture_depth(), original.texture_depth());
+    }
+
+    #[test]
+    fn rejects_bad_magic() {
+        let mut buf = serialize(&sample());
+        buf[0] = b'X';
+        assert_eq!(read_error(&buf).kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_bad_version() {
+        let mut buf = serialize(&sample());
+        buf[MAGIC.len()..MAGIC.len() + 4].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        assert_eq!(read_error(&buf).kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_truncated_file() {
+        let buf = serialize(&sample());
+        // in the middle of the probes
+        assert_eq!(
+            read_error(&buf[..buf.len() - 1]).kind(),
+            io::ErrorKind::InvalidData
+        );
+        // in the middle of the header
+        assert_eq!(
+            read_error(&buf[..MAGIC.len() + 2]).kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn rejects_huge_number_without_allocating() {
+        let mut buf = serialize(&sample());
+        // number follows the magic, 5 scalars and 4 vectors
+        let at = MAGIC.len() + 4 * 6 + 4 * 3 * 4;
+        let huge = i32::MAX;
+        for i in 0..3 {
+            buf[at + i * 4..at + i * 4 + 4].copy_from_slice(&huge.to_le_bytes());
+        }
+        assert_eq!(read_error(&buf).kind(), io::ErrorKind::InvalidData);
+    }
+}
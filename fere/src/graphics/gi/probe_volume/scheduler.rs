@@ -0,0 +1,238 @@
+use super::*;
+use crate::configs::ProbeUpdatePriority;
+
+/// Decides which probe of a volume to update next.
+///
+/// A probe is updated over 6 faces, which may span several frames.
+/// Once it's started, it's finished before moving to another one.
+pub struct ProbeScheduler {
+    /// All the probes, in the order of the linear walk.
+    probes: Vec<IVec3>,
+    /// The probe being updated, and the face of it to render next.
+    current: Option<(IVec3, SixDir)>,
+    /// The next probe of the linear walk.
+    cursor: usize,
+    dirty: Vec<bool>,
+    /// `update_count` when each probe was updated last time.
+    last_update: Vec<u64>,
+    /// The number of probes fully updated so far.
+    update_count: u64,
+}
+
+impl ProbeScheduler {
+    pub fn new(number: IVec3) -> Self {
+        let probes: Vec<IVec3> = IteratorVec3::new(number).collect();
+        let n = probes.len();
+        Self {
+            probes,
+            current: None,
+            cursor: 0,
+            dirty: vec![false; n],
+            last_update: vec![0; n],
+            update_count: 0,
+        }
+    }
+
+    pub fn update_count(&self) -> u64 {
+        self.update_count
+    }
+
+    /// Makes the probe updated before the others.
+    pub fn mark_dirty(&mut self, probe_volume: &ProbeVolume, index: IVec3) {
+        self.dirty[GridAccessor3(probe_volume.number()).get(&index)] = true;
+    }
+
+    /// The probe and its face to render now.
+    pub fn current(
+        &mut self,
+        probe_volume: &ProbeVolume,
+        priority: ProbeUpdatePriority,
+        camera_pos: Vec3,
+    ) -> (IVec3, SixDir) {
+        if let Some(x) = self.current {
+            return x;
+        }
+        let ga = GridAccessor3(probe_volume.number());
+        let distance =
+            |index: &IVec3| (probe_volume.probes()[ga.get(index)].pos - camera_pos).norm();
+
+        let dirty = self
+            .probes
+            .iter()
+            .filter(|x| self.dirty[ga.get(x)])
+            .min_by(|x, y| {
+                distance(x)
+                    .partial_cmp(&distance(y))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+        let probe = if let Some(x) = dirty {
+            *x
+        } else {
+            match priority {
+                ProbeUpdatePriority::Linear => {
+                    let x = self.probes[self.cursor];
+                    self.cursor = (self.cursor + 1) % self.probes.len();
+                    x
+                }
+                ProbeUpdatePriority::CameraDistance => {
+                    // How stale it is, discounted by the distance in the unit of cells
+                    let cell = probe_volume.cell_size().norm().max(std::f32::EPSILON);
+                    let score = |index: &IVec3| {
+                        let age = (self.update_count - self.last_update[ga.get(index)]) as f32;
+                        (age + 1.0) / (1.0 + distance(index) / cell)
+                    };
+                    *self
+                        .probes
+                        .iter()
+                        .max_by(|x, y| {
+                            score(x)
+                                .partial_cmp(&score(y))
+                                .unwrap_or(std::cmp::Ordering::Equal)
+                        })
+                        .unwrap()
+                }
+            }
+        };
+        self.current = Some((probe, 0));
+        (probe, 0)
+    }
+
+    /// Moves on after rendering the current face. Returns `true` if the probe got all the 6 faces.
+    pub fn advance(&mut self, probe_volume: &ProbeVolume) -> bool {
+        let (probe, face) = self.current.expect("advance() without current()");
+        if face < 5 {
+            self.current = Some((probe, face + 1));
+            return false;
+        }
+        let i = GridAccessor3(probe_volume.number()).get(&probe);
+        self.update_count += 1;
+        self.dirty[i] = false;
+        self.last_update[i] = self.update_count;
+        self.current = None;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 3 x 2 x 2 probes at x = -6, 0, 6 / y = -4, 4 / z = 4, 8
+    fn sample() -> ProbeVolume {
+        let configs = IrradianceVolumeConfigs {
+            sh_params: 9,
+            spacing: Vec3::new(6.0, 6.0, 6.0),
+            ..Default::default()
+        };
+        ProbeVolume::new(Vec3::new(20.0, 16.0, 12.0), &configs, 2)
+    }
+
+    /// Renders all the 6 faces of the current probe.
+    fn update(
+        scheduler: &mut ProbeScheduler,
+        probe_volume: &ProbeVolume,
+        priority: ProbeUpdatePriority,
+        camera_pos: Vec3,
+    ) -> IVec3 {
+        let (probe, _) = scheduler.current(probe_volume, priority, camera_pos);
+        for face in 0..6 {
+            assert_eq!(
+                scheduler.current(probe_volume, priority, camera_pos),
+                (probe, face)
+            );
+            assert_eq!(scheduler.advance(probe_volume), face == 5);
+        }
+        probe
+    }
+
+    #[test]
+    fn linear_walks_in_order_and_wraps_around() {
+        let probe_volume = sample();
+        let mut scheduler = ProbeScheduler::new(probe_volume.number());
+        let order: Vec<IVec3> = IteratorVec3::new(probe_volume.number()).collect();
+        assert_eq!(order.len(), 12);
+
+        for i in 0..order.len() * 2 + 1 {
+            let probe = update(
+                &mut scheduler,
+                &probe_volume,
+                ProbeUpdatePriority::Linear,
+                Vec3::zeros(),
+            );
+            assert_eq!(probe, order[i % order.len()]);
+            assert_eq!(scheduler.update_count(), i as u64 + 1);
+        }
+    }
+
+    #[test]
+    fn camera_distance_prefers_near_and_then_stale_probes() {
+        let probe_volume = sample();
+        let mut scheduler = ProbeScheduler::new(probe_volume.number());
+        let priority = ProbeUpdatePriority::CameraDistance;
+        let camera_pos = Vec3::new(6.0, 4.0, 8.0);
+
+        let first = update(&mut scheduler, &probe_volume, priority, camera_pos);
+        assert_eq!(first, IVec3::new(2, 1, 1));
+        // The one just updated waits for the others near the camera
+        let second = update(&mut scheduler, &probe_volume, priority, camera_pos);
+        assert_ne!(second, first);
+
+        // The far ones still get their turns
+        let mut visited = vec![first, second];
+        for _ in 0..22 {
+            visited.push(update(&mut scheduler, &probe_volume, priority, camera_pos));
+        }
+        for probe in IteratorVec3::new(probe_volume.number()) {
+            assert!(visited.contains(&probe), "{:?} is never updated", probe);
+        }
+    }
+
+    #[test]
+    fn nan_camera_doesnt_panic() {
+        let probe_volume = sample();
+        let camera_pos = Vec3::new(f32::NAN, 0.0, 0.0);
+        for priority in [
+            ProbeUpdatePriority::Linear,
+            ProbeUpdatePriority::CameraDistance,
+        ]
+        .iter()
+        {
+            let mut scheduler = ProbeScheduler::new(probe_volume.number());
+            scheduler.mark_dirty(&probe_volume, IVec3::new(1, 0, 0));
+            scheduler.mark_dirty(&probe_volume, IVec3::new(2, 1, 1));
+            for _ in 0..4 {
+                update(&mut scheduler, &probe_volume, *priority, camera_pos);
+            }
+            assert_eq!(scheduler.update_count(), 4);
+        }
+    }
+
+    #[test]
+    fn dirty_probes_come_first() {
+        let probe_volume = sample();
+        let mut scheduler = ProbeScheduler::new(probe_volume.number());
+        let order: Vec<IVec3> = IteratorVec3::new(probe_volume.number()).collect();
+        let camera_pos = Vec3::new(-6.0, -4.0, 4.0);
+
+        let far = IVec3::new(2, 1, 1);
+        let near = IVec3::new(1, 0, 0);
+        scheduler.mark_dirty(&probe_volume, far);
+        scheduler.mark_dirty(&probe_volume, near);
+        // The nearest one of them first, before the linear walk
+        let priority = ProbeUpdatePriority::Linear;
+        assert_eq!(
+            update(&mut scheduler, &probe_volume, priority, camera_pos),
+            near
+        );
+        assert_eq!(
+            update(&mut scheduler, &probe_volume, priority, camera_pos),
+            far
+        );
+        // The linear walk isn't disturbed by them
+        assert_eq!(
+            update(&mut scheduler, &probe_volume, priority, camera_pos),
+            order[0]
+        );
+        assert_eq!(scheduler.update_count(), 3);
+    }
+}
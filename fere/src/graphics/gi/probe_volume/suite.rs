@@ -23,9 +23,13 @@ fn init_buffer<T: Copy>(zero: T, size: usize) -> [Vec<T>; 6] {
 }
 
 impl ProbeVolumeSuite {
-    pub fn new(room_size: Vec3, scale: f32, resolution: usize) -> Self {
-        let probe_volume = ProbeVolume::new(room_size, scale, resolution);
+    pub fn new(room_size: Vec3, configs: &IrradianceVolumeConfigs, resolution: usize) -> Self {
+        Self::with_probe_volume(ProbeVolume::new(room_size, configs, resolution))
+    }
 
+    /// Starts from the given (e.g. baked) probe volume, which is loaded to the textures immediately.
+    pub fn with_probe_volume(probe_volume: ProbeVolume) -> Self {
+        let resolution = probe_volume.fb_size();
         let sh_texture_illumination =
             TextureInternal3D::new(InternalTexType::Float3, probe_volume.texture_size());
         let sh_texture_depth =
@@ -35,7 +39,7 @@ impl ProbeVolumeSuite {
         let buffer_illumination = init_buffer(Vec3::new(0.0, 0.0, 0.0), resolution);
         let buffer_depth = init_buffer(0.0, resolution);
 
-        Self {
+        let mut suite = Self {
             probe_volume,
             sh_texture_illumination,
             sh_texture_depth,
@@ -43,6 +47,17 @@ impl ProbeVolumeSuite {
             buffer_illumination,
             buffer_depth,
             resolution,
+        };
+        suite.load_texture();
+        suite
+    }
+
+    fn load_texture(&mut self) {
+        unsafe {
+            self.sh_texture_illumination
+                .load(self.probe_volume.texture_illumination().as_ptr().cast());
+            self.sh_texture_depth
+                .load(self.probe_volume.texture_depth().as_ptr().cast());
         }
     }
 
@@ -77,11 +92,6 @@ impl ProbeVolumeSuite {
             &self.buffer_depth,
         );
         self.probe_volume.update_texture();
-        unsafe {
-            self.sh_texture_illumination
-                .load(self.probe_volume.texture_illumination().as_ptr().cast());
-            self.sh_texture_depth
-                .load(self.probe_volume.texture_depth().as_ptr().cast());
-        }
+        self.load_texture();
     }
 }
@@ -0,0 +1,59 @@
+use crate::graphics::graphics::texture_internal::TextureInternalCube;
+use crate::{ReflectionProbeConfig, ReflectionProbeUpdate};
+
+/// A local cube map captured from its chamber, and prefiltered the same as `EnvironmentMap`.
+pub struct ReflectionProbe {
+    config: ReflectionProbeConfig,
+    /// The raw emission of the surroundings, with the full mipmap chain as the source of the prefiltering.
+    capture: TextureInternalCube,
+    /// The specular reflection, prefiltered with the roughness increasing along the mipmap levels.
+    specular: TextureInternalCube,
+    levels: usize,
+    /// Whether it has to be captured again in the next frame.
+    dirty: bool,
+}
+
+impl ReflectionProbe {
+    pub fn new(
+        config: ReflectionProbeConfig,
+        capture: TextureInternalCube,
+        specular: TextureInternalCube,
+        levels: usize,
+    ) -> Self {
+        Self {
+            config,
+            capture,
+            specular,
+            levels,
+            dirty: true,
+        }
+    }
+
+    pub fn config(&self) -> &ReflectionProbeConfig {
+        &self.config
+    }
+
+    pub fn capture(&self) -> &TextureInternalCube {
+        &self.capture
+    }
+
+    pub fn specular(&self) -> &TextureInternalCube {
+        &self.specular
+    }
+
+    pub fn levels(&self) -> usize {
+        self.levels
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    pub fn set_dirty(&mut self, dirty: bool) {
+        self.dirty = dirty;
+    }
+
+    pub fn is_round_robin(&self) -> bool {
+        self.config.update == ReflectionProbeUpdate::RoundRobin
+    }
+}
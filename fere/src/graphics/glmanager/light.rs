@@ -4,6 +4,8 @@ pub struct Light {
     pub pos: Vec4,
     pub color: Vec3,
     pub shadow: bool,
+    /// (constant, linear, quadratic)
+    pub attenuation: Vec3,
 }
 
 pub struct LightUni {
@@ -21,6 +23,12 @@ pub struct LightDir {
     pub round: bool,
     pub smoothnes: f32,
     pub trans: Mat4,
+
+    /// 0: none, 1: PCF, 2: PCSS
+    pub filter: i32,
+    pub light_size: f32,
+    pub bias: f32,
+    pub normal_offset: f32,
 }
 
 /// Room parameter
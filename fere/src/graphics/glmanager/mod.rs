@@ -91,6 +91,11 @@ impl GlManager {
         let program = self.programs.get(key).unwrap();
         Arc::clone(program)
     }
+
+    /// Every program in `shaders/config.yaml`.
+    pub fn programs(&self) -> impl Iterator<Item = &Arc<Shader>> {
+        self.programs.values()
+    }
 }
 
 /// When OpenGL meets an error, this callback function will be called on the same stack.
@@ -8,7 +8,8 @@ use std::ffi::CString;
 use strum::IntoEnumIterator;
 use strum_macros::{AsRefStr, EnumIter};
 
-const UNIFORM_MAX: usize = 16;
+/// The maximum number of texture units and lights for a program
+pub const UNIFORM_MAX: usize = 16;
 
 #[derive(AsRefStr, EnumIter, Debug, PartialEq, Clone, Copy)]
 pub enum Uniform {
@@ -58,6 +59,63 @@ pub enum Uniform {
     Ambient,
     Cpos,
     Shadow,
+    LightNum,
+    MajorLightNum,
+    Far,
+    CascadeDir,
+    CascadeRange,
+
+    // Tone mapping
+    ToneMap,
+    Exposure,
+    AutoExposure,
+    ExposureRange,
+    AdaptationRate,
+
+    // Ambient occlusion
+    CameraTrans,
+    SsaoStrength,
+    SsaoRadius,
+
+    // Screen-space reflections
+    SsrMaxSteps,
+    SsrMaxDistance,
+    SsrThickness,
+
+    // Anti-aliasing
+    InvCameraTrans,
+    PrevCameraTrans,
+    PrevModel,
+    Jitter,
+    HistoryValid,
+
+    // Skybox
+    SkyIntensity,
+    SkyLod,
+
+    // Image-based lighting
+    CubeFace,
+    PrefilterRoughness,
+    IblIntensity,
+    IblLevels,
+
+    // Reflection probes
+    ProbePos,
+    BoxMin,
+    BoxMax,
+
+    // Volumetric fog
+    FogDensity,
+    FogHeightFalloff,
+    FogAlbedo,
+    FroxelRange,
+    FroxelSize,
+    Slice,
+
+    // Bloom
+    BloomThreshold,
+    BloomWeight,
+    TargetSize,
 
     // Non-trivial
     Color,
@@ -89,6 +147,12 @@ pub enum UniformLight {
     Trans,
     Round,
     Smoothness, // 0~1, availiable only if GULightRound == true
+    Filter,
+    LightSize,
+    Bias,
+    NormalOffset,
+    Attenuation,
+    Radius,
     Max_,
 }
 
@@ -282,6 +346,16 @@ impl Shader {
             gl::Uniform4fv(u, 1, light.pos.as_ptr());
             u = self.uloc_lights[i][UniformLight::Color as usize];
             gl::Uniform3fv(u, 1, light.color.as_ptr());
+            u = self.uloc_lights[i][UniformLight::Attenuation as usize];
+            gl::Uniform3fv(u, 1, light.attenuation.as_ptr());
+        }
+    }
+
+    pub fn uniform_light_uni(&self, light: &LightUni, i: usize) {
+        self.uniform_light(&light.light, i);
+        unsafe {
+            let u = self.uloc_lights[i][UniformLight::Radius as usize];
+            gl::Uniform1f(u, light.radius);
         }
     }
 
@@ -289,12 +363,266 @@ impl Shader {
         self.uniform_light(&light.light, i);
         unsafe {
             let mut u: GLint;
+            u = self.uloc_lights[i][UniformLight::Radius as usize];
+            gl::Uniform1f(u, light.radius);
             u = self.uloc_lights[i][UniformLight::Trans as usize];
             gl::UniformMatrix4fv(u, 1, gl::FALSE, light.trans.as_ptr());
             u = self.uloc_lights[i][UniformLight::Round as usize];
             gl::Uniform1i(u, light.round as i32);
             u = self.uloc_lights[i][UniformLight::Smoothness as usize];
             gl::Uniform1f(u, light.smoothnes);
+            u = self.uloc_lights[i][UniformLight::Filter as usize];
+            gl::Uniform1i(u, light.filter);
+            u = self.uloc_lights[i][UniformLight::LightSize as usize];
+            gl::Uniform1f(u, light.light_size);
+            u = self.uloc_lights[i][UniformLight::Bias as usize];
+            gl::Uniform1f(u, light.bias);
+            u = self.uloc_lights[i][UniformLight::NormalOffset as usize];
+            gl::Uniform1f(u, light.normal_offset);
+        }
+    }
+
+    /// For the forward shading, lights of [0, major) are major lights and [major, total) are point lights.
+    pub fn uniform_light_num(&self, major: usize, total: usize) {
+        debug_assert!(major <= total && total <= UNIFORM_MAX);
+        unsafe {
+            gl::Uniform1i(self.uloc[Uniform::MajorLightNum as usize], major as i32);
+            gl::Uniform1i(self.uloc[Uniform::LightNum as usize], total as i32);
+        }
+    }
+
+    /// The far plane of a cube shadow map, which normalizes the distance stored in it.
+    pub fn uniform_far(&self, far: f32) {
+        unsafe {
+            gl::Uniform1f(self.uloc[Uniform::Far as usize], far);
+        }
+    }
+
+    /// `operator` 0: clamp, 1: Reinhard, 2: ACES filmic
+    ///
+    /// With `auto_exposure`, `exposure` is the key that the adapted luminance is mapped to.
+    pub fn uniform_tone_map(
+        &self,
+        operator: i32,
+        exposure: f32,
+        auto_exposure: bool,
+        range: &Vec2,
+    ) {
+        unsafe {
+            gl::Uniform1i(self.uloc[Uniform::ToneMap as usize], operator);
+            gl::Uniform1f(self.uloc[Uniform::Exposure as usize], exposure);
+            gl::Uniform1i(
+                self.uloc[Uniform::AutoExposure as usize],
+                auto_exposure as i32,
+            );
+            gl::Uniform2fv(
+                self.uloc[Uniform::ExposureRange as usize],
+                1,
+                range.as_ptr(),
+            );
+        }
+    }
+
+    /// `camera_trans` is the projection * view of the camera.
+    pub fn uniform_ssao(&self, camera_trans: &Mat4, strength: f32, radius: f32) {
+        unsafe {
+            gl::UniformMatrix4fv(
+                self.uloc[Uniform::CameraTrans as usize],
+                1,
+                gl::FALSE,
+                camera_trans.as_ptr(),
+            );
+            gl::Uniform1f(self.uloc[Uniform::SsaoStrength as usize], strength);
+            gl::Uniform1f(self.uloc[Uniform::SsaoRadius as usize], radius);
+        }
+    }
+
+    /// `camera_trans` is the projection * view of the camera.
+    pub fn uniform_ssr(
+        &self,
+        camera_trans: &Mat4,
+        max_steps: usize,
+        max_distance: f32,
+        thickness: f32,
+    ) {
+        unsafe {
+            gl::UniformMatrix4fv(
+                self.uloc[Uniform::CameraTrans as usize],
+                1,
+                gl::FALSE,
+                camera_trans.as_ptr(),
+            );
+            gl::Uniform1i(self.uloc[Uniform::SsrMaxSteps as usize], max_steps as i32);
+            gl::Uniform1f(self.uloc[Uniform::SsrMaxDistance as usize], max_distance);
+            gl::Uniform1f(self.uloc[Uniform::SsrThickness as usize], thickness);
+        }
+    }
+
+    /// `inv_camera_trans` is the inverse of the current projection * view, and
+    /// `prev_camera_trans` is the projection * view of the previous frame, both with the jitter.
+    pub fn uniform_reprojection(&self, inv_camera_trans: &Mat4, prev_camera_trans: &Mat4) {
+        unsafe {
+            gl::UniformMatrix4fv(
+                self.uloc[Uniform::InvCameraTrans as usize],
+                1,
+                gl::FALSE,
+                inv_camera_trans.as_ptr(),
+            );
+            gl::UniformMatrix4fv(
+                self.uloc[Uniform::PrevCameraTrans as usize],
+                1,
+                gl::FALSE,
+                prev_camera_trans.as_ptr(),
+            );
+        }
+    }
+
+    pub fn uniform_inv_camera_trans(&self, inv_camera_trans: &Mat4) {
+        unsafe {
+            gl::UniformMatrix4fv(
+                self.uloc[Uniform::InvCameraTrans as usize],
+                1,
+                gl::FALSE,
+                inv_camera_trans.as_ptr(),
+            );
+        }
+    }
+
+    /// `lod` is the level of mipmap to look up, for the environment lighting.
+    pub fn uniform_sky(&self, intensity: f32, lod: f32) {
+        unsafe {
+            gl::Uniform1f(self.uloc[Uniform::SkyIntensity as usize], intensity);
+            gl::Uniform1f(self.uloc[Uniform::SkyLod as usize], lod);
+        }
+    }
+
+    /// `face` follows the order of `GL_TEXTURE_CUBE_MAP_POSITIVE_X + face`, and `size` is of the level being rendered.
+    pub fn uniform_prefilter(&self, face: usize, roughness: f32, size: i32) {
+        unsafe {
+            gl::Uniform1i(self.uloc[Uniform::CubeFace as usize], face as i32);
+            gl::Uniform1f(self.uloc[Uniform::PrefilterRoughness as usize], roughness);
+            gl::Uniform2f(
+                self.uloc[Uniform::TargetSize as usize],
+                size as f32,
+                size as f32,
+            );
+        }
+    }
+
+    /// `levels` is the number of the mipmap levels of the prefiltered specular.
+    pub fn uniform_ibl(&self, intensity: f32, levels: usize) {
+        unsafe {
+            gl::Uniform1f(self.uloc[Uniform::IblIntensity as usize], intensity);
+            gl::Uniform1f(self.uloc[Uniform::IblLevels as usize], levels as f32);
+        }
+    }
+
+    /// The capture position and the projection box of a reflection probe.
+    pub fn uniform_reflection_probe(&self, pos: &Vec3, box_min: &Vec3, box_max: &Vec3) {
+        unsafe {
+            gl::Uniform3fv(self.uloc[Uniform::ProbePos as usize], 1, pos.as_ptr());
+            gl::Uniform3fv(self.uloc[Uniform::BoxMin as usize], 1, box_min.as_ptr());
+            gl::Uniform3fv(self.uloc[Uniform::BoxMax as usize], 1, box_max.as_ptr());
+        }
+    }
+
+    /// The fog is bounded by the box of [`box_min`, `box_max`].
+    pub fn uniform_fog(
+        &self,
+        density: f32,
+        height_falloff: f32,
+        albedo: &Vec3,
+        box_min: &Vec3,
+        box_max: &Vec3,
+    ) {
+        unsafe {
+            gl::Uniform3fv(self.uloc[Uniform::BoxMin as usize], 1, box_min.as_ptr());
+            gl::Uniform3fv(self.uloc[Uniform::BoxMax as usize], 1, box_max.as_ptr());
+            gl::Uniform1f(self.uloc[Uniform::FogDensity as usize], density);
+            gl::Uniform1f(
+                self.uloc[Uniform::FogHeightFalloff as usize],
+                height_falloff,
+            );
+            gl::Uniform3fv(self.uloc[Uniform::FogAlbedo as usize], 1, albedo.as_ptr());
+        }
+    }
+
+    /// `range` is [near, far) of the froxels along the view, where the slices are distributed exponentially.
+    pub fn uniform_froxels(&self, range: &Vec2, size: &IVec3) {
+        unsafe {
+            gl::Uniform2fv(self.uloc[Uniform::FroxelRange as usize], 1, range.as_ptr());
+            gl::Uniform3iv(self.uloc[Uniform::FroxelSize as usize], 1, size.as_ptr());
+        }
+    }
+
+    pub fn uniform_slice(&self, slice: i32) {
+        unsafe {
+            gl::Uniform1i(self.uloc[Uniform::Slice as usize], slice);
+        }
+    }
+
+    /// The model transformation of the object in the previous frame, with the previous camera.
+    pub fn uniform_prev_model(&self, prev_camera_trans: &Mat4, prev_model: &Mat4) {
+        unsafe {
+            gl::UniformMatrix4fv(
+                self.uloc[Uniform::PrevCameraTrans as usize],
+                1,
+                gl::FALSE,
+                prev_camera_trans.as_ptr(),
+            );
+            gl::UniformMatrix4fv(
+                self.uloc[Uniform::PrevModel as usize],
+                1,
+                gl::FALSE,
+                prev_model.as_ptr(),
+            );
+        }
+    }
+
+    /// The jitter of the camera in the current and the previous frame, to be removed from the motion.
+    pub fn uniform_jitter(&self, current: &Vec2, previous: &Vec2) {
+        unsafe {
+            gl::Uniform4f(
+                self.uloc[Uniform::Jitter as usize],
+                current.x,
+                current.y,
+                previous.x,
+                previous.y,
+            );
+        }
+    }
+
+    pub fn uniform_history_valid(&self, valid: bool) {
+        unsafe {
+            gl::Uniform1i(self.uloc[Uniform::HistoryValid as usize], valid as i32);
+        }
+    }
+
+    /// `threshold` < 0 to take every pixel. `size` is the size of the target.
+    pub fn uniform_bloom(&self, threshold: f32, weight: f32, size: &IVec2) {
+        unsafe {
+            gl::Uniform1f(self.uloc[Uniform::BloomThreshold as usize], threshold);
+            gl::Uniform1f(self.uloc[Uniform::BloomWeight as usize], weight);
+            gl::Uniform2f(
+                self.uloc[Uniform::TargetSize as usize],
+                size.x as f32,
+                size.y as f32,
+            );
+        }
+    }
+
+    pub fn uniform_adaptation_rate(&self, rate: f32) {
+        unsafe {
+            gl::Uniform1f(self.uloc[Uniform::AdaptationRate as usize], rate);
+        }
+    }
+
+    /// Restricts the deferred lighting to `[near, far)` along `dir` from the camera. `None` to disable.
+    pub fn uniform_cascade(&self, cascade: Option<(&Vec3, f32, f32)>) {
+        let (dir, near, far) = cascade.unwrap_or((&Vec3::zeros(), 0.0, 0.0));
+        unsafe {
+            gl::Uniform3fv(self.uloc[Uniform::CascadeDir as usize], 1, dir.as_ptr());
+            gl::Uniform2f(self.uloc[Uniform::CascadeRange as usize], near, far);
         }
     }
 
@@ -3,6 +3,7 @@ mod pass;
 mod prgs;
 mod read_buffer;
 pub mod texture_internal;
+pub mod tiled;
 
 use super::{
     glmanager::{
@@ -11,13 +12,20 @@ use super::{
     },
     render_unit::RenderUnit,
 };
+use crate::graphics::gi::probe_volume::calculation;
+use crate::graphics::gi::reflection_probe::ReflectionProbe;
 use crate::graphics::glmanager::light::*;
 use crate::graphics::resources::Meshes;
 use crate::resources::*;
+use crate::ReflectionProbeConfig;
 use fere_common::*;
-use gl::types::GLuint;
+use gl::types::{GLenum, GLuint};
 use serde::{Deserialize, Serialize};
-use texture_internal::{FrameBuffer, TextureInternal3D};
+use std::collections::HashMap;
+use std::sync::Arc;
+use texture_internal::{
+    CubeFrameBuffer, FrameBuffer, InternalTexType, TextureInternal3D, TextureInternalCube,
+};
 
 pub fn deferred_mode(color: bool, depth: bool, index: bool) {
     unsafe {
@@ -38,19 +46,46 @@ pub fn deferred_mode(color: bool, depth: bool, index: bool) {
 
 #[derive(Debug)]
 pub struct TextureFetcher {
-    _framebuffer: GLuint,
-    _color_attachment: GLuint,
+    framebuffer: GLuint,
+    color_attachment: GLuint,
+    size: IVec2,
 }
 
 impl TextureFetcher {
-    pub fn fetch(&self) -> u64 {
-        0
+    /// Reads a single unsigned integer texel at the given pixel (from the left-bottom).
+    ///
+    /// Returns 0 if the position is out of the buffer.
+    pub fn fetch(&self, pos: IVec2) -> u64 {
+        if self.framebuffer == 0
+            || pos.x < 0
+            || pos.y < 0
+            || pos.x >= self.size.x
+            || pos.y >= self.size.y
+        {
+            return 0;
+        }
+        let mut value: u32 = 0;
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.framebuffer);
+            gl::ReadBuffer(gl::COLOR_ATTACHMENT0 + self.color_attachment);
+            gl::ReadPixels(
+                pos.x,
+                pos.y,
+                1,
+                1,
+                gl::RED_INTEGER,
+                gl::UNSIGNED_INT,
+                (&mut value as *mut u32).cast(),
+            );
+        }
+        value as u64
     }
 
     pub fn noop() -> Self {
         Self {
-            _framebuffer: 0,
-            _color_attachment: 0,
+            framebuffer: 0,
+            color_attachment: 0,
+            size: IVec2::new(0, 0),
         }
     }
 }
@@ -61,10 +96,73 @@ pub struct GraphicsConfig {
     pub shadow_resolution: usize,
     pub probe_resolution: usize,
     pub max_major_lights: usize,
+    pub sun_cascade_num: usize,
+    pub sun_shadow_resolution: usize,
 
     pub video_record: bool,
 }
 
+/// Parameters of the tone mapping, as the shader takes.
+pub struct ToneMapParams {
+    /// 0: clamp, 1: Reinhard, 2: ACES filmic
+    pub operator: i32,
+    /// The multiplier, or the key for the auto exposure.
+    pub exposure: f32,
+    pub auto_exposure: bool,
+    /// (min, max) of the auto exposure
+    pub exposure_range: Vec2,
+    pub adaptation_rate: f32,
+}
+
+/// The maximum length of the downsample chain of the bloom.
+pub const MAX_BLOOM_LEVELS: usize = 6;
+
+/// The number of major lights shaded in a single pass, whose shadow maps take the texture units from 8 to 15.
+pub const MAJOR_LIGHT_BATCH: usize = 8;
+
+/// The length of the jitter sequence of TAA.
+const TAA_SAMPLES: usize = 8;
+
+/// The `index`-th element of the Halton sequence, in [0, 1).
+fn halton(mut index: usize, base: usize) -> f32 {
+    let mut result = 0.0;
+    let mut f = 1.0;
+    while index > 0 {
+        f /= base as f32;
+        result += f * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
+
+/// The size of the prefiltered specular of an environment map.
+const ENVIRONMENT_SIZE: i32 = 128;
+/// The number of the mipmap levels of the prefiltered specular, from the roughness 0 to 1.
+const ENVIRONMENT_LEVELS: usize = 5;
+/// The irradiance of an environment map is projected from its mipmap of this size in log2.
+const IRRADIANCE_SAMPLE_LOG2: usize = 5;
+/// The size of the lookup table of the split-sum BRDF.
+const BRDF_LUT_SIZE: i32 = 256;
+
+/// The number of froxels of the volumetric fog, along the width, the height and the depth of the view.
+const FROXEL_SIZE: [i32; 3] = [160, 90, 64];
+/// The fog beyond this distance from the camera is not rendered.
+const FOG_MAX_DISTANCE: f32 = 200.0;
+
+/// The participating medium of a chamber, for the volumetric fog.
+#[derive(Clone, Debug)]
+pub struct FogMedium {
+    /// The bounds of the chamber
+    pub bpos: Vec3,
+    pub size: Vec3,
+    pub density: f32,
+    pub height_falloff: f32,
+    pub albedo: Vec3,
+}
+
+/// The size of the luminance buffer for the auto exposure.
+const LUMINANCE_SIZE: u32 = 256;
+
 pub struct Graphics {
     gl_manager: GlManager,
 
@@ -73,9 +171,52 @@ pub struct Graphics {
     // passes
     pass_deferred1: FrameBuffer,
     pass_final: FrameBuffer,
+    pass_ldr: FrameBuffer,
     pass_shadow: Vec<FrameBuffer>,
+    pass_shadow_cube: CubeFrameBuffer,
+    pass_shadow_sun: Vec<FrameBuffer>,
     pass_probe: FrameBuffer,
 
+    // auto exposure
+    pass_luminance: FrameBuffer,
+    pass_adapted: [FrameBuffer; 2],
+    exposure_flip: bool,
+
+    // ambient occlusion; raw and blurred
+    pass_ssao: [FrameBuffer; 2],
+
+    // screen-space reflections; (reflected color, confidence)
+    pass_ssr: FrameBuffer,
+
+    // anti-aliasing; the history of TAA, also used as a scratch for FXAA
+    pass_motion: FrameBuffer,
+    pass_history: [FrameBuffer; 2],
+    history_flip: bool,
+    history_valid: bool,
+    taa_frame: usize,
+    // (current, previous) projection * view of the camera, with the jitter
+    taa_trans: (Mat4, Mat4),
+    // (current, previous) jitter of the camera in the normalized device coordinates
+    taa_jitter: (Vec2, Vec2),
+    // the current projection and view of the camera, with the jitter
+    taa_camera: (Mat4, Mat4),
+    // (current, previous) model transformations of the objects by their ids
+    object_trans: (HashMap<u32, Mat4>, HashMap<u32, Mat4>),
+    // (mesh, model, previous model) of the objects in the G-buffer of this frame
+    motion_objects: Vec<(Arc<Mesh>, Mat4, Mat4)>,
+
+    // volumetric fog; (in-scattering, extinction) and (accumulated in-scattering, transmittance) of froxels
+    fog_scattering: TextureInternal3D,
+    fog_integrated: TextureInternal3D,
+    // (inverse projection * view, camera position, [near, far) of the froxels) of the current frame
+    fog_view: (Mat4, Vec3, Vec2),
+
+    // the split-sum BRDF of (n dot v, roughness), for the image-based lighting
+    pass_brdf_lut: FrameBuffer,
+
+    // bloom; half, quarter, ... of the screen
+    pass_bloom: Vec<FrameBuffer>,
+
     pass_yuv: Option<FrameBuffer>,
 
     // useful meshes
@@ -92,11 +233,40 @@ impl Graphics {
 
         let pass_deferred1 = pass::create_deferred(screen_size);
         let pass_final = pass::create_final(screen_size);
+        let pass_ldr = pass::create_ldr(screen_size);
 
         let pass_shadow = (0..max_major_lights)
             .map(|_| pass::create_shadow(config.shadow_resolution as u32))
             .collect::<Vec<_>>();
+        let pass_shadow_cube = pass::create_shadow_cube(config.shadow_resolution as u32);
+        let pass_shadow_sun = (0..config.sun_cascade_num)
+            .map(|_| pass::create_shadow(config.sun_shadow_resolution as u32))
+            .collect::<Vec<_>>();
         let pass_probe = pass::create_probe(config.probe_resolution as u32);
+        let pass_luminance = pass::create_luminance(LUMINANCE_SIZE);
+        let pass_adapted = [pass::create_luminance(1), pass::create_luminance(1)];
+        let pass_ssao = [
+            pass::create_luminance_screen(screen_size),
+            pass::create_luminance_screen(screen_size),
+        ];
+        let pass_ssr = pass::create_ssr(screen_size);
+        let pass_motion = pass::create_motion(screen_size, pass_deferred1.depth_get());
+        let pass_history = [
+            pass::create_history(screen_size),
+            pass::create_history(screen_size),
+        ];
+        let froxel_size = IVec3::new(FROXEL_SIZE[0], FROXEL_SIZE[1], FROXEL_SIZE[2]);
+        let fog_scattering = TextureInternal3D::new(InternalTexType::Float4, froxel_size);
+        let fog_integrated = TextureInternal3D::new(InternalTexType::Float4, froxel_size);
+        fog_integrated.filter_linear();
+        let pass_brdf_lut = pass::create_brdf_lut(BRDF_LUT_SIZE);
+        let pass_bloom = (1..=MAX_BLOOM_LEVELS)
+            .map(|i| pass::create_bloom(screen_size / 2_i32.pow(i as u32)))
+            .collect::<Vec<_>>();
+        for fb in &pass_adapted {
+            fb.bind();
+            fb.clear_color_all();
+        }
         let pass_yuv = if config.video_record {
             Some(pass::create_yuv(screen_size))
         } else {
@@ -107,17 +277,42 @@ impl Graphics {
 
         let prgs = prgs::Programs::new(&gl_manager);
 
-        Graphics {
+        let graphics = Graphics {
             gl_manager,
             screen_size,
             pass_deferred1,
             pass_final,
+            pass_ldr,
             pass_shadow,
+            pass_shadow_cube,
+            pass_shadow_sun,
             pass_probe,
+            pass_luminance,
+            pass_adapted,
+            exposure_flip: false,
+            pass_ssao,
+            pass_ssr,
+            pass_motion,
+            pass_history,
+            history_flip: false,
+            history_valid: false,
+            taa_frame: 0,
+            taa_trans: (Mat4::identity(), Mat4::identity()),
+            taa_jitter: (Vec2::zeros(), Vec2::zeros()),
+            taa_camera: (Mat4::identity(), Mat4::identity()),
+            object_trans: Default::default(),
+            motion_objects: Vec::new(),
+            fog_scattering,
+            fog_integrated,
+            fog_view: (Mat4::identity(), Vec3::zeros(), Vec2::new(0.1, 1.0)),
+            pass_brdf_lut,
+            pass_bloom,
             pass_yuv,
             meshes,
             prgs,
-        }
+        };
+        pass::bake_brdf_lut(&graphics);
+        graphics
     }
 
     pub fn meshes(&self) -> &Meshes {
@@ -129,11 +324,28 @@ impl Graphics {
     /// You can fetch object index from the buffer.
     pub fn get_object_index_fetcher(&self) -> TextureFetcher {
         TextureFetcher {
-            _framebuffer: self.pass_deferred1.raw_get(),
-            _color_attachment: 6,
+            framebuffer: self.pass_deferred1.raw_get(),
+            color_attachment: 6,
+            size: self.screen_size,
         }
     }
 
+    /// The number of shadow maps for major lights, which bounds the major lights of a chamber.
+    pub fn max_major_lights(&self) -> usize {
+        self.pass_shadow.len()
+    }
+
+    pub fn sun_cascade_num(&self) -> usize {
+        self.pass_shadow_sun.len()
+    }
+
+    /// The width (and the height) of the shadow map of each sun cascade.
+    pub fn sun_shadow_resolution(&self) -> usize {
+        self.pass_shadow_sun
+            .first()
+            .map_or(0, |x| x.depth_get().size_get().x as usize)
+    }
+
     pub fn screen_size(&self) -> IVec2 {
         self.screen_size
     }
@@ -197,6 +409,37 @@ impl Graphics {
         mesh.draw();
     }
 
+    /// Shades the whole screen at once with the point lights in `tiled`, instead of their light volumes.
+    ///
+    /// The G-buffer takes the units [0, 8), and the lights take [8, 11).
+    pub fn draw_lights_tiled(&self, program: &Shader, tiled: &tiled::TiledLights, cpos: Vec3) {
+        self.bind_gbuffer(program, 0);
+        for (i, tex) in tiled.textures().iter().enumerate() {
+            let unit = 8 + i;
+            unsafe {
+                gl::ActiveTexture(gl::TEXTURE0 + unit as gl::types::GLenum);
+                gl::BindTexture(gl::TEXTURE_2D, tex.tex_get().raw_get());
+                gl::Uniform1i(program.uloc_get_tex()[unit], unit as i32)
+            }
+        }
+        program.uniform_camera(&cpos);
+
+        deferred_mode(true, false, false);
+        unsafe {
+            gl::Disable(gl::DEPTH_TEST);
+            gl::Disable(gl::CULL_FACE);
+            gl::Disable(gl::STENCIL_TEST);
+            gl::Enable(gl::BLEND);
+            gl::BlendEquation(gl::FUNC_ADD);
+            gl::BlendFunc(gl::ONE, gl::ONE);
+        }
+        self.fill_screen(program);
+        unsafe {
+            gl::Enable(gl::DEPTH_TEST);
+            gl::Enable(gl::CULL_FACE);
+        }
+    }
+
     pub fn draw_lightvolume_uni(
         &self,
         program: &Shader,
@@ -204,7 +447,7 @@ impl Graphics {
         cpos: Vec3,
         _range: bool,
     ) {
-        program.uniform_light(&light.light, 0);
+        program.uniform_light_uni(light, 0);
         program.uniform_camera(&cpos);
 
         let radius = light.radius;
@@ -282,6 +525,16 @@ impl Graphics {
         pass::bind_shadow(self, index);
     }
 
+    /// Binds the given face of the cube shadow map for omni-directional lights.
+    pub fn bind_shadow_cube(&self, face: usize) {
+        pass::bind_shadow_cube(self, face);
+    }
+
+    /// Binds the shadow map of the given cascade for sun lights.
+    pub fn bind_shadow_sun(&self, cascade: usize) {
+        pass::bind_shadow_sun(self, cascade);
+    }
+
     pub fn bind_probe(&self) {
         pass::bind_probe(self)
     }
@@ -297,6 +550,36 @@ impl Graphics {
         }
     }
 
+    /// Binds the shadow maps of the major lights from `first`, to the texture units from 8.
+    pub fn bind_shadow_maps(&self, program: &Shader, first: usize, count: usize) {
+        debug_assert!(count <= MAJOR_LIGHT_BATCH);
+        for (i, pass) in self.pass_shadow[first..first + count].iter().enumerate() {
+            program.uniform_texture(8 + i, pass.depth_get().tex_get().raw_get());
+        }
+    }
+
+    /// Binds a cascade of the sun's shadow map to the texture unit 8, as the first one of `bind_shadow_maps()`.
+    pub fn bind_shadow_map_sun(&self, program: &Shader, cascade: usize) {
+        program.uniform_texture(
+            8,
+            self.pass_shadow_sun[cascade]
+                .depth_get()
+                .tex_get()
+                .raw_get(),
+        );
+    }
+
+    pub fn bind_shadow_map_cube(&self, program: &Shader) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE6);
+            gl::BindTexture(
+                gl::TEXTURE_CUBE_MAP,
+                self.pass_shadow_cube.depth_get().tex_get().raw_get(),
+            );
+            gl::Uniform1i(program.uloc_get_tex()[6], 6)
+        }
+    }
+
     pub fn bind_gbuffer(&self, program: &Shader, offset: usize) {
         pass::bind_gbuffer(self, program, offset)
     }
@@ -311,6 +594,190 @@ impl Graphics {
         pass::bind_probe_volume(self, program, offset, illumination, depth)
     }
 
+    /// Computes the ambient occlusion from the G-buffer.
+    ///
+    /// `params` is (strength, radius), or `None` to leave everything unoccluded.
+    pub fn ambient_occlusion(&self, camera_trans: &Mat4, cpos: &Vec3, params: Option<(f32, f32)>) {
+        pass::ambient_occlusion(self, camera_trans, cpos, params)
+    }
+
+    /// Binds the (blurred) ambient occlusion to `unit`, which the ambient lightings multiply.
+    pub fn bind_ambient_occlusion(&self, program: &Shader, unit: usize) {
+        program.uniform_texture(unit, self.pass_ssao[1].outputs_get()[0].tex_get().raw_get());
+    }
+
+    /// Traces the reflections of the shaded scene in `pass_final` through the depth buffer,
+    /// and adds them to `pass_final`.
+    ///
+    /// `params` is (max steps, max distance, thickness), or `None` to leave every ray missed.
+    pub fn screen_space_reflections(
+        &self,
+        camera_trans: &Mat4,
+        cpos: &Vec3,
+        params: Option<(usize, f32, f32)>,
+    ) {
+        pass::screen_space_reflections(self, camera_trans, cpos, params)
+    }
+
+    /// Binds the screen-space reflections to `unit`, whose alpha is how much they cover.
+    pub fn bind_screen_space_reflections(&self, program: &Shader, unit: usize) {
+        program.uniform_texture(unit, self.pass_ssr.outputs_get()[0].tex_get().raw_get());
+    }
+
+    /// Empties the froxels for a new frame seen by the camera.
+    ///
+    /// `camera_trans` is the projection * view, and `near` is the near plane of the camera.
+    pub fn begin_fog(&mut self, camera_trans: &Mat4, cpos: &Vec3, near: f32, far: f32) {
+        self.fog_view = (
+            glm::inverse(camera_trans),
+            *cpos,
+            Vec2::new(near, far.min(FOG_MAX_DISTANCE).max(near * 2.0)),
+        );
+        pass::clear_fog(self)
+    }
+
+    /// Scatters the ambient light of a chamber in its fog, and adds its extinction.
+    pub fn scatter_fog_medium(&self, medium: &FogMedium, ambient: &Vec3) {
+        pass::scatter_fog_medium(self, medium, ambient)
+    }
+
+    /// Scatters a major light in the fog of a chamber, through the shadow map of `index`.
+    pub fn scatter_fog_light(&self, medium: &FogMedium, light: &LightDir, index: usize) {
+        pass::scatter_fog_light(self, medium, light, index)
+    }
+
+    /// Integrates the froxels along the view, and applies the fog to `pass_final`.
+    pub fn apply_fog(&self) {
+        pass::apply_fog(self)
+    }
+
+    /// Draws the sky on the pixels of `pass_final` where the G-buffer is empty.
+    pub fn draw_skybox(&self, sky: &CubeTexture, camera_trans: &Mat4, cpos: &Vec3, intensity: f32) {
+        pass::draw_skybox(self, sky, camera_trans, cpos, intensity)
+    }
+
+    pub fn bind_cube_texture(&self, program: &Shader, unit: usize, texture: &CubeTexture) {
+        texture.bind_at(unit as u32);
+        unsafe {
+            gl::Uniform1i(program.uloc_get_tex()[unit], unit as i32);
+        }
+    }
+
+    /// Prefilters the specular and projects the irradiance of `sky` for the image-based lighting.
+    pub fn create_environment_map(&self, sky: &CubeTexture) -> EnvironmentMap {
+        let specular = TextureInternalCube::new(
+            InternalTexType::HalfFloat3,
+            IVec2::new(ENVIRONMENT_SIZE, ENVIRONMENT_SIZE),
+        );
+        specular.allocate_mipmap(ENVIRONMENT_LEVELS);
+        pass::prefilter_environment(self, sky.raw_get(), &specular, ENVIRONMENT_LEVELS);
+
+        // The irradiance is so blurry that a small mipmap is enough
+        let level = (sky.max_lod() as usize).saturating_sub(IRRADIANCE_SAMPLE_LOG2);
+        let (faces, size) = sky.read_faces(level);
+        let irradiance = calculation::project_cube_irradiance(&faces, size);
+        EnvironmentMap::new(specular, ENVIRONMENT_LEVELS, irradiance)
+    }
+
+    /// Binds the prefiltered specular of `map` to `unit`, and the BRDF lookup table to `unit + 1`.
+    pub fn bind_environment_map(&self, program: &Shader, unit: usize, map: &EnvironmentMap) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + unit as GLenum);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, map.specular().tex_get().raw_get());
+            gl::Uniform1i(program.uloc_get_tex()[unit], unit as i32);
+        }
+        program.uniform_texture(
+            unit + 1,
+            self.pass_brdf_lut.outputs_get()[0].tex_get().raw_get(),
+        );
+    }
+
+    /// Allocates the cube maps of a reflection probe, which is captured in the size of `pass_probe`.
+    pub fn create_reflection_probe(&self, config: ReflectionProbeConfig) -> ReflectionProbe {
+        let size = self.pass_probe.outputs_get()[0].size_get();
+        let capture = TextureInternalCube::new(InternalTexType::HalfFloat3, size);
+        capture.allocate_mipmap((size.x as f32).log2() as usize + 1);
+        let specular = TextureInternalCube::new(
+            InternalTexType::HalfFloat3,
+            IVec2::new(ENVIRONMENT_SIZE, ENVIRONMENT_SIZE),
+        );
+        specular.allocate_mipmap(ENVIRONMENT_LEVELS);
+        ReflectionProbe::new(config, capture, specular, ENVIRONMENT_LEVELS)
+    }
+
+    /// Copies what's rendered with `bind_probe()` to a face of the capture of `probe`.
+    pub fn capture_reflection_face(&self, probe: &ReflectionProbe, face: usize) {
+        pass::capture_cube_face(self, probe.capture(), face)
+    }
+
+    /// Prefilters the capture of `probe` after all the six faces are captured.
+    pub fn prefilter_reflection_probe(&self, probe: &ReflectionProbe) {
+        probe.capture().generate_mipmap();
+        pass::prefilter_environment(
+            self,
+            probe.capture().tex_get().raw_get(),
+            probe.specular(),
+            probe.levels(),
+        );
+    }
+
+    /// Binds the prefiltered specular of `probe` to 8, and the BRDF lookup table to 9.
+    pub fn bind_reflection_probe(&self, program: &Shader, probe: &ReflectionProbe) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE8);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, probe.specular().tex_get().raw_get());
+            gl::Uniform1i(program.uloc_get_tex()[8], 8);
+        }
+        program.uniform_texture(9, self.pass_brdf_lut.outputs_get()[0].tex_get().raw_get());
+    }
+
+    pub fn fxaa(&self) {
+        pass::fxaa(self)
+    }
+
+    /// Shifts the camera by a sub-pixel offset which changes every frame, for `taa()`.
+    pub fn jitter_camera(&mut self, camera: &mut SetCamera) {
+        let i = self.taa_frame % TAA_SAMPLES + 1;
+        let offset = Vec2::new(halton(i, 2) - 0.5, halton(i, 3) - 0.5);
+        let jitter = Vec2::new(
+            offset.x * 2.0 / self.screen_size.x as f32,
+            offset.y * 2.0 / self.screen_size.y as f32,
+        );
+        camera.jitter(jitter);
+        self.taa_jitter.0 = jitter;
+        self.taa_camera = (*camera.projection_get(), *camera.view_get());
+        self.taa_trans.0 = camera.projection_get() * camera.view_get();
+    }
+
+    /// Records an object drawn in the G-buffer, whose motion vectors are written in `taa()`.
+    ///
+    /// Objects are tracked between frames by `id`, except for 0 which is never regarded as moving.
+    pub fn record_motion(&mut self, mesh: Arc<Mesh>, id: u32, trans: &Mat4) {
+        let prev = if id == 0 {
+            *trans
+        } else {
+            self.object_trans.0.insert(id, *trans);
+            *self.object_trans.1.get(&id).unwrap_or(trans)
+        };
+        self.motion_objects.push((mesh, *trans, prev));
+    }
+
+    /// Resolves `pass_final` with the history of the previous frames.
+    ///
+    /// The camera must have been jittered with `jitter_camera()`.
+    pub fn taa(&mut self) {
+        pass::taa(self)
+    }
+
+    /// Adds the glow of bright pixels to `pass_final`.
+    pub fn bloom(&self, threshold: f32, levels: usize, intensity: f32) {
+        pass::bloom(self, threshold, levels, intensity)
+    }
+
+    pub fn tone_map(&mut self, params: &ToneMapParams) {
+        pass::tone_map(self, params)
+    }
+
     pub fn render_final(&self) {
         pass::render_final(self)
     }
@@ -140,14 +140,28 @@ fn bind_no_transparent(prg: &Shader) {
     }
 }
 
+/// The blend rate from a timepoint to the next one, where `t` is the progress between them.
+///
+/// The transition takes the last `smoothness` of the interval, eased in and out.
+/// A `smoothness` of 0 steps at the end of the interval.
+fn timepoint_blend(t: f64, smoothness: f32) -> f64 {
+    let smoothness = (smoothness as f64).max(0.0).min(1.0);
+    if smoothness <= 0.0 {
+        return 0.0;
+    }
+    let x = ((t - (1.0 - smoothness)) / smoothness).max(0.0).min(1.0);
+    x * x * (3.0 - 2.0 * x)
+}
+
 /// time: 0 to 1
 pub fn bind_emissive_static(prg: &Shader, surface: &EmissiveStaticI, time: f64) {
     bind_general_(prg, &surface.general);
     bind_no_transparent(prg);
 
-    let first = (time * TIMEPOINT_NUMBER as f64) as usize;
+    let time = time.rem_euclid(1.0) * TIMEPOINT_NUMBER as f64;
+    let first = (time as usize).min(TIMEPOINT_NUMBER - 1);
     let second = (first + 1) % TIMEPOINT_NUMBER;
-    let blend = time * TIMEPOINT_NUMBER as f64 - first as f64;
+    let blend = timepoint_blend(time - first as f64, surface.timepoints[first].smoothness);
 
     unsafe {
         gl::Uniform1i(prg.uloc_get(Uniform::EmissionBlend), 1);
@@ -195,21 +209,45 @@ fn bind_no_emission(prg: &Shader) {
 }
 
 /// It must be called together with `bind_general()`
+///
+/// The frame of a `Video` must be already updated for `surface.time`.
 pub fn bind_emissive_dynamic(
     prg: &Shader,
     surface: &EmissiveDynamic,
     materials: &[EmissiveMaterialI],
 ) {
+    unsafe {
+        gl::Uniform1i(prg.uloc_get(Uniform::EmissionBlend), 0);
+    }
+
     let (emission, intensity) = match surface.current {
         CurrentEmission::Arbitrary(x) => (TexVar::U(x.xyz()), TexVar::U(x.w as u8)),
         CurrentEmission::Material(x) => match &materials[x] {
             EmissiveMaterial::Plain(emission, intensity) => (emission.clone(), intensity.clone()),
-            _ => panic!(),
+            EmissiveMaterial::Video(video, intensity) => {
+                unsafe {
+                    gl::Uniform1i(prg.uloc_get(Uniform::EmissionOn), 0);
+                    gl::Uniform1i(prg.uloc_get(Uniform::EmissionIntensityOn), 1);
+                    gl::Uniform1f(
+                        prg.uloc_get(Uniform::EmissionIntensity),
+                        *intensity as f32 / 255.0,
+                    );
+                }
+                video.bind_at(3);
+                return;
+            }
+            EmissiveMaterial::AnimatedUniform(keyframes) => {
+                let (emission, intensity) = animated_uniform_at(keyframes, surface.time);
+                unsafe {
+                    gl::Uniform1i(prg.uloc_get(Uniform::EmissionOn), 1);
+                    gl::Uniform3fv(prg.uloc_get(Uniform::Emission), 1, emission.as_ptr());
+                    gl::Uniform1i(prg.uloc_get(Uniform::EmissionIntensityOn), 1);
+                    gl::Uniform1f(prg.uloc_get(Uniform::EmissionIntensity), intensity);
+                }
+                return;
+            }
         },
     };
-    unsafe {
-        gl::Uniform1i(prg.uloc_get(Uniform::EmissionBlend), 0);
-    }
 
     bind_texture3(prg, &emission, 3, Uniform::EmissionOn, Uniform::Emission);
     bind_texture1(
@@ -244,3 +282,36 @@ pub fn bind_simple_general(prg: &Shader, surface: &GeneralSimple) {
         Uniform::EmissionIntensity,
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timepoint_blend_steps_without_smoothness() {
+        for &t in &[0.0, 0.5, 0.999] {
+            assert_eq!(timepoint_blend(t, 0.0), 0.0);
+            assert_eq!(timepoint_blend(t, -1.0), 0.0);
+        }
+    }
+
+    #[test]
+    fn timepoint_blend_eases_in_the_last_part() {
+        assert_eq!(timepoint_blend(0.0, 0.5), 0.0);
+        assert_eq!(timepoint_blend(0.5, 0.5), 0.0);
+        assert!((timepoint_blend(0.75, 0.5) - 0.5).abs() < 1e-9);
+        assert_eq!(timepoint_blend(1.0, 0.5), 1.0);
+
+        // Clamped out of the interval
+        assert_eq!(timepoint_blend(-0.5, 1.0), 0.0);
+        assert_eq!(timepoint_blend(1.5, 1.0), 1.0);
+        assert_eq!(timepoint_blend(0.25, 2.0), timepoint_blend(0.25, 1.0));
+
+        let mut last = 0.0;
+        for i in 0..=100 {
+            let blend = timepoint_blend(i as f64 / 100.0, 0.3);
+            assert!(blend >= last);
+            last = blend;
+        }
+    }
+}
@@ -1,10 +1,16 @@
 use super::super::glmanager::shader::Shader;
+use super::FogMedium;
 use super::{
     deferred_mode,
-    texture_internal::{FrameBuffer, InternalTexType, TextureInternal2D, TextureInternal3D},
+    texture_internal::{
+        CubeFrameBuffer, FrameBuffer, InternalTexType, TextureInternal2D, TextureInternal3D,
+        TextureInternalCube,
+    },
 };
+use crate::graphics::glmanager::light::LightDir;
+use crate::resources::CubeTexture;
 use fere_common::*;
-use gl::types::GLenum;
+use gl::types::{GLenum, GLuint};
 
 // Rendering Pipeline
 // TODO
@@ -27,6 +33,73 @@ pub fn create_deferred(size: IVec2) -> FrameBuffer {
 
 pub fn create_final(size: IVec2) -> FrameBuffer {
     let outputs = vec![TextureInternal2D::new(InternalTexType::Float3, size)];
+    // The bloom samples it in a lower resolution
+    outputs[0].filter_linear();
+    FrameBuffer::new(outputs, None)
+}
+
+/// The G-buffer has no room for another attachment (8 is the least guaranteed),
+/// so the motion vectors are written in a separate target right after it.
+///
+/// It shares the depth of the G-buffer, so that only the visible surface of each object writes its motion.
+pub fn create_motion(size: IVec2, depth: &TextureInternal2D) -> FrameBuffer {
+    let outputs = vec![TextureInternal2D::new(InternalTexType::HalfFloat2, size)];
+    let framebuffer = FrameBuffer::new(outputs, None);
+    unsafe {
+        gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer.raw_get());
+        gl::FramebufferTexture2D(
+            gl::FRAMEBUFFER,
+            gl::DEPTH_ATTACHMENT,
+            gl::TEXTURE_2D,
+            depth.tex_get().raw_get(),
+            0,
+        );
+        gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+    }
+    framebuffer
+}
+
+pub fn create_history(size: IVec2) -> FrameBuffer {
+    let outputs = vec![TextureInternal2D::new(InternalTexType::Float3, size)];
+    // It's sampled at the reprojected positions
+    outputs[0].filter_linear();
+    FrameBuffer::new(outputs, None)
+}
+
+pub fn create_brdf_lut(size: i32) -> FrameBuffer {
+    let outputs = vec![TextureInternal2D::new(
+        InternalTexType::HalfFloat2,
+        IVec2::new(size, size),
+    )];
+    outputs[0].filter_linear();
+    FrameBuffer::new(outputs, None)
+}
+
+pub fn create_ldr(size: IVec2) -> FrameBuffer {
+    let outputs = vec![TextureInternal2D::new(InternalTexType::Color, size)];
+    FrameBuffer::new(outputs, None)
+}
+
+pub fn create_luminance(size: u32) -> FrameBuffer {
+    let size = IVec2::new(size as i32, size as i32);
+    let outputs = vec![TextureInternal2D::new(InternalTexType::Luminance, size)];
+    FrameBuffer::new(outputs, None)
+}
+
+pub fn create_luminance_screen(size: IVec2) -> FrameBuffer {
+    let outputs = vec![TextureInternal2D::new(InternalTexType::Luminance, size)];
+    FrameBuffer::new(outputs, None)
+}
+
+pub fn create_ssr(size: IVec2) -> FrameBuffer {
+    let outputs = vec![TextureInternal2D::new(InternalTexType::Float4, size)];
+    FrameBuffer::new(outputs, None)
+}
+
+pub fn create_bloom(size: IVec2) -> FrameBuffer {
+    let size = IVec2::new(size.x.max(1), size.y.max(1));
+    let outputs = vec![TextureInternal2D::new(InternalTexType::HalfFloat3, size)];
+    outputs[0].filter_linear();
     FrameBuffer::new(outputs, None)
 }
 
@@ -39,6 +112,11 @@ pub fn create_shadow(size: u32) -> FrameBuffer {
     FrameBuffer::new(outputs, Some(depth))
 }
 
+pub fn create_shadow_cube(size: u32) -> CubeFrameBuffer {
+    let size = IVec2::new(size as i32, size as i32);
+    CubeFrameBuffer::new(TextureInternalCube::new(InternalTexType::Depth, size))
+}
+
 pub fn create_probe(size: u32) -> FrameBuffer {
     let size = IVec2::new(size as i32, size as i32);
     let outputs = vec![
@@ -75,6 +153,37 @@ pub fn bind_shadow(graphics: &super::Graphics, index: usize) {
     }
 }
 
+pub fn bind_shadow_sun(graphics: &super::Graphics, cascade: usize) {
+    deferred_mode(false, true, false);
+    graphics.pass_shadow_sun[cascade].bind();
+    graphics.pass_shadow_sun[cascade].clear_depth();
+    graphics.pass_shadow_sun[cascade].clear_color_all();
+    unsafe {
+        gl::Disable(gl::BLEND);
+        gl::Enable(gl::DEPTH_TEST);
+        gl::DepthFunc(gl::LESS);
+        gl::Disable(gl::MULTISAMPLE);
+        gl::Enable(gl::CULL_FACE);
+        gl::CullFace(gl::FRONT); // Important!
+        gl::Disable(gl::STENCIL_TEST);
+    }
+}
+
+pub fn bind_shadow_cube(graphics: &super::Graphics, face: usize) {
+    deferred_mode(false, true, false);
+    graphics.pass_shadow_cube.bind_face(face);
+    graphics.pass_shadow_cube.clear_depth();
+    unsafe {
+        gl::Disable(gl::BLEND);
+        gl::Enable(gl::DEPTH_TEST);
+        gl::DepthFunc(gl::LESS);
+        gl::Disable(gl::MULTISAMPLE);
+        gl::Enable(gl::CULL_FACE);
+        gl::CullFace(gl::FRONT); // Important!
+        gl::Disable(gl::STENCIL_TEST);
+    }
+}
+
 pub fn bind_probe(graphics: &super::Graphics) {
     deferred_mode(true, true, false);
     graphics.pass_probe.bind();
@@ -157,6 +266,559 @@ pub fn bind_probe_volume(
     }
 }
 
+pub fn ambient_occlusion(
+    graphics: &super::Graphics,
+    camera_trans: &Mat4,
+    cpos: &Vec3,
+    params: Option<(f32, f32)>,
+) {
+    let (strength, radius) = if let Some(x) = params {
+        x
+    } else {
+        graphics.pass_ssao[1].bind();
+        unsafe {
+            gl::ClearColor(1.0, 1.0, 1.0, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+            gl::ClearColor(0.0, 0.0, 0.0, 0.0);
+        }
+        return;
+    };
+
+    deferred_mode(true, false, false);
+    unsafe {
+        gl::Disable(gl::BLEND);
+        gl::Disable(gl::DEPTH_TEST);
+        gl::Disable(gl::MULTISAMPLE);
+        gl::Disable(gl::CULL_FACE);
+        gl::Disable(gl::STENCIL_TEST);
+    }
+
+    // Step1: Occlusion from the positions and normals
+    graphics.pass_ssao[0].bind();
+    let program = graphics.prgs.ssao.bind();
+    bind_gbuffer(graphics, program, 0);
+    program.uniform_ssao(camera_trans, strength, radius);
+    program.uniform_camera(cpos);
+    graphics.fill_screen(program);
+
+    // Step2: Blur the noise
+    graphics.pass_ssao[1].bind();
+    let program = graphics.prgs.ssao_blur.bind();
+    program.uniform_texture(
+        0,
+        graphics.pass_ssao[0].outputs_get()[0].tex_get().raw_get(),
+    );
+    graphics.fill_screen(program);
+}
+
+fn post_process_mode() {
+    deferred_mode(true, false, false);
+    unsafe {
+        gl::Disable(gl::BLEND);
+        gl::Disable(gl::DEPTH_TEST);
+        gl::Disable(gl::MULTISAMPLE);
+        gl::Disable(gl::CULL_FACE);
+        gl::Disable(gl::STENCIL_TEST);
+    }
+}
+
+pub fn screen_space_reflections(
+    graphics: &super::Graphics,
+    camera_trans: &Mat4,
+    cpos: &Vec3,
+    params: Option<(usize, f32, f32)>,
+) {
+    post_process_mode();
+    let (max_steps, max_distance, thickness) = if let Some(x) = params {
+        x
+    } else {
+        // Zero confidence everywhere, so that the probes and the environment maps take the whole specular.
+        // `clear_color()` would leave the alpha at 1.
+        graphics.pass_ssr.bind();
+        let zero: [f32; 4] = [0.0; 4];
+        unsafe {
+            gl::ClearBufferfv(gl::COLOR, 0, zero.as_ptr());
+        }
+        return;
+    };
+
+    // Step1: March the reflected rays through the depth buffer
+    graphics.pass_ssr.bind();
+    let program = graphics.prgs.ssr.bind();
+    bind_gbuffer(graphics, program, 0);
+    program.uniform_texture(8, graphics.pass_final.outputs_get()[0].tex_get().raw_get());
+    program.uniform_texture(9, graphics.pass_deferred1.depth_get().tex_get().raw_get());
+    program.uniform_ssr(camera_trans, max_steps, max_distance, thickness);
+    program.uniform_inv_camera_trans(&glm::inverse(camera_trans));
+    program.uniform_camera(cpos);
+    graphics.fill_screen(program);
+
+    // Step2: Add them with the specular weight
+    graphics.pass_final.bind();
+    unsafe {
+        gl::Enable(gl::BLEND);
+        gl::BlendEquation(gl::FUNC_ADD);
+        gl::BlendFunc(gl::ONE, gl::ONE);
+    }
+    let program = graphics.prgs.ssr_resolve.bind();
+    bind_gbuffer(graphics, program, 0);
+    program.uniform_texture(8, graphics.pass_ssr.outputs_get()[0].tex_get().raw_get());
+    program.uniform_texture(
+        9,
+        graphics.pass_brdf_lut.outputs_get()[0].tex_get().raw_get(),
+    );
+    graphics.bind_ambient_occlusion(program, 10);
+    program.uniform_camera(cpos);
+    graphics.fill_screen(program);
+    unsafe {
+        gl::Disable(gl::BLEND);
+    }
+}
+
+/// Runs `program` on each slice of `dest`, which is told by `u_slice`.
+fn fill_slices(graphics: &super::Graphics, program: &Shader, dest: &TextureInternal3D) {
+    let size = dest.size_get();
+    let mut framebuffer = 0;
+    unsafe {
+        gl::GenFramebuffers(1, &mut framebuffer);
+        gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+        gl::Viewport(0, 0, size.x, size.y);
+    }
+    for slice in 0..size.z {
+        unsafe {
+            gl::FramebufferTextureLayer(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                dest.tex_get().raw_get(),
+                0,
+                slice,
+            );
+        }
+        program.uniform_slice(slice);
+        graphics.fill_screen(program);
+    }
+    unsafe {
+        gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        gl::DeleteFramebuffers(1, &framebuffer);
+    }
+}
+
+fn uniform_froxels(graphics: &super::Graphics, program: &Shader) {
+    let (inv_camera_trans, cpos, range) = &graphics.fog_view;
+    program.uniform_inv_camera_trans(inv_camera_trans);
+    program.uniform_camera(cpos);
+    program.uniform_froxels(range, &graphics.fog_scattering.size_get());
+}
+
+fn uniform_fog_medium(program: &Shader, medium: &FogMedium) {
+    program.uniform_fog(
+        medium.density,
+        medium.height_falloff,
+        &medium.albedo,
+        &medium.bpos,
+        &(medium.bpos + medium.size),
+    );
+}
+
+pub fn clear_fog(graphics: &super::Graphics) {
+    post_process_mode();
+    let size = graphics.fog_scattering.size_get();
+    // The alpha is the extinction, which must start from zero regardless of the last `gl::ClearColor()`
+    let zero: [f32; 4] = [0.0; 4];
+    let mut framebuffer = 0;
+    unsafe {
+        gl::GenFramebuffers(1, &mut framebuffer);
+        gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+        for slice in 0..size.z {
+            gl::FramebufferTextureLayer(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                graphics.fog_scattering.tex_get().raw_get(),
+                0,
+                slice,
+            );
+            gl::ClearBufferfv(gl::COLOR, 0, zero.as_ptr());
+        }
+        gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        gl::DeleteFramebuffers(1, &framebuffer);
+    }
+}
+
+pub fn scatter_fog_medium(graphics: &super::Graphics, medium: &FogMedium, ambient: &Vec3) {
+    post_process_mode();
+    unsafe {
+        gl::Enable(gl::BLEND);
+        gl::BlendEquation(gl::FUNC_ADD);
+        gl::BlendFunc(gl::ONE, gl::ONE);
+    }
+    let program = graphics.prgs.fog_medium.bind();
+    uniform_froxels(graphics, program);
+    uniform_fog_medium(program, medium);
+    program.uniform_ambient(ambient);
+    fill_slices(graphics, program, &graphics.fog_scattering);
+    unsafe {
+        gl::Disable(gl::BLEND);
+    }
+}
+
+pub fn scatter_fog_light(
+    graphics: &super::Graphics,
+    medium: &FogMedium,
+    light: &LightDir,
+    index: usize,
+) {
+    post_process_mode();
+    unsafe {
+        gl::Enable(gl::BLEND);
+        gl::BlendEquation(gl::FUNC_ADD);
+        gl::BlendFunc(gl::ONE, gl::ONE);
+    }
+    let program = graphics.prgs.fog_light.bind();
+    uniform_froxels(graphics, program);
+    uniform_fog_medium(program, medium);
+    program.uniform_light_dir(light, 0);
+    graphics.bind_shadow_map(program, index);
+    fill_slices(graphics, program, &graphics.fog_scattering);
+    unsafe {
+        gl::Disable(gl::BLEND);
+    }
+}
+
+pub fn apply_fog(graphics: &super::Graphics) {
+    post_process_mode();
+
+    // Step1: Accumulate the froxels from the camera
+    let program = graphics.prgs.fog_integrate.bind();
+    unsafe {
+        gl::ActiveTexture(gl::TEXTURE0);
+        gl::BindTexture(gl::TEXTURE_3D, graphics.fog_scattering.tex_get().raw_get());
+        gl::Uniform1i(program.uloc_get_tex()[0], 0);
+    }
+    uniform_froxels(graphics, program);
+    fill_slices(graphics, program, &graphics.fog_integrated);
+
+    // Step2: scene * transmittance + in-scattering
+    graphics.pass_final.bind();
+    unsafe {
+        gl::Enable(gl::BLEND);
+        gl::BlendEquation(gl::FUNC_ADD);
+        gl::BlendFunc(gl::ONE, gl::SRC_ALPHA);
+    }
+    let program = graphics.prgs.fog_apply.bind();
+    unsafe {
+        gl::ActiveTexture(gl::TEXTURE0);
+        gl::BindTexture(gl::TEXTURE_3D, graphics.fog_integrated.tex_get().raw_get());
+        gl::Uniform1i(program.uloc_get_tex()[0], 0);
+    }
+    program.uniform_texture(1, graphics.pass_deferred1.depth_get().tex_get().raw_get());
+    uniform_froxels(graphics, program);
+    graphics.fill_screen(program);
+    unsafe {
+        gl::Disable(gl::BLEND);
+    }
+}
+
+/// Copies the color of `src` to `dest` of the same size.
+fn copy_color(src: &FrameBuffer, dest: &FrameBuffer) {
+    let size = src.outputs_get()[0].size_get();
+    unsafe {
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, src.raw_get());
+        gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, dest.raw_get());
+        gl::BlitFramebuffer(
+            0,
+            0,
+            size.x,
+            size.y,
+            0,
+            0,
+            size.x,
+            size.y,
+            gl::COLOR_BUFFER_BIT,
+            gl::NEAREST,
+        );
+        gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+    }
+}
+
+pub fn draw_skybox(
+    graphics: &super::Graphics,
+    sky: &CubeTexture,
+    camera_trans: &Mat4,
+    cpos: &Vec3,
+    intensity: f32,
+) {
+    post_process_mode();
+    graphics.pass_final.bind();
+    let program = graphics.prgs.skybox.bind();
+    program.uniform_texture(0, graphics.pass_deferred1.depth_get().tex_get().raw_get());
+    graphics.bind_cube_texture(program, 1, sky);
+    program.uniform_inv_camera_trans(&glm::inverse(camera_trans));
+    program.uniform_camera(cpos);
+    program.uniform_sky(intensity, 0.0);
+    graphics.fill_screen(program);
+}
+
+pub fn bake_brdf_lut(graphics: &super::Graphics) {
+    post_process_mode();
+    graphics.pass_brdf_lut.bind();
+    let program = graphics.prgs.brdf_lut.bind();
+    graphics.fill_screen(program);
+}
+
+/// Prefilters the cube map `source` into the mipmap levels of `dest`, with the roughness increasing along them.
+pub fn prefilter_environment(
+    graphics: &super::Graphics,
+    source: GLuint,
+    dest: &TextureInternalCube,
+    levels: usize,
+) {
+    post_process_mode();
+    let program = graphics.prgs.prefilter.bind();
+    unsafe {
+        gl::ActiveTexture(gl::TEXTURE0);
+        gl::BindTexture(gl::TEXTURE_CUBE_MAP, source);
+        gl::Uniform1i(program.uloc_get_tex()[0], 0);
+    }
+
+    let mut framebuffer = 0;
+    unsafe {
+        gl::GenFramebuffers(1, &mut framebuffer);
+        gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+    }
+    for level in 0..levels {
+        let size = (dest.size_get().x >> level).max(1);
+        let roughness = level as f32 / (levels - 1).max(1) as f32;
+        for face in 0..6 {
+            unsafe {
+                gl::Viewport(0, 0, size, size);
+                gl::FramebufferTexture2D(
+                    gl::FRAMEBUFFER,
+                    gl::COLOR_ATTACHMENT0,
+                    gl::TEXTURE_CUBE_MAP_POSITIVE_X + face as GLenum,
+                    dest.tex_get().raw_get(),
+                    level as i32,
+                );
+            }
+            program.uniform_prefilter(face, roughness, size);
+            graphics.fill_screen(program);
+        }
+    }
+    unsafe {
+        gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        gl::DeleteFramebuffers(1, &framebuffer);
+    }
+}
+
+/// Copies the emission of `pass_probe` to a face of `dest`, which must be of the same size.
+pub fn capture_cube_face(graphics: &super::Graphics, dest: &TextureInternalCube, face: usize) {
+    let size = graphics.pass_probe.outputs_get()[1].size_get();
+    unsafe {
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, graphics.pass_probe.raw_get());
+        gl::ReadBuffer(gl::COLOR_ATTACHMENT1);
+        gl::BindTexture(gl::TEXTURE_CUBE_MAP, dest.tex_get().raw_get());
+        gl::CopyTexSubImage2D(
+            gl::TEXTURE_CUBE_MAP_POSITIVE_X + face as GLenum,
+            0,
+            0,
+            0,
+            0,
+            0,
+            size.x,
+            size.y,
+        );
+        gl::ReadBuffer(gl::COLOR_ATTACHMENT0);
+        gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+    }
+}
+
+pub fn fxaa(graphics: &super::Graphics) {
+    post_process_mode();
+
+    // The history of TAA is free to use as a scratch
+    graphics.pass_history[0].bind();
+    let program = graphics.prgs.fxaa.bind();
+    program.uniform_texture(0, graphics.pass_final.outputs_get()[0].tex_get().raw_get());
+    graphics.fill_screen(program);
+    copy_color(&graphics.pass_history[0], &graphics.pass_final);
+}
+
+pub fn taa(graphics: &mut super::Graphics) {
+    post_process_mode();
+    let (current, previous) = graphics.taa_trans;
+    let (jitter, prev_jitter) = graphics.taa_jitter;
+
+    // Step1: Motion vectors of the camera, from the depth of the G-buffer
+    graphics.pass_motion.bind();
+    let program = graphics.prgs.motion.bind();
+    program.uniform_texture(0, graphics.pass_deferred1.depth_get().tex_get().raw_get());
+    program.uniform_reprojection(&glm::inverse(&current), &previous);
+    program.uniform_jitter(&jitter, &prev_jitter);
+    graphics.fill_screen(program);
+
+    // Step2: Motion vectors of the objects, over the camera's where they are visible
+    unsafe {
+        gl::Enable(gl::DEPTH_TEST);
+        gl::DepthFunc(gl::LEQUAL);
+    }
+    let program = graphics.prgs.motion_object.bind();
+    program.uniform_transformations(&graphics.taa_camera.0, &graphics.taa_camera.1);
+    program.uniform_jitter(&jitter, &prev_jitter);
+    for (mesh, trans, prev_trans) in graphics.motion_objects.drain(..) {
+        program.uniform_model(&trans, false);
+        program.uniform_prev_model(&previous, &prev_trans);
+        mesh.bind();
+        mesh.draw();
+    }
+    unsafe {
+        gl::Disable(gl::DEPTH_TEST);
+    }
+
+    // Step3: Blend the current frame with the reprojected history
+    let (prev, next) = if graphics.history_flip {
+        (1, 0)
+    } else {
+        (0, 1)
+    };
+    graphics.history_flip = !graphics.history_flip;
+    graphics.pass_history[next].bind();
+    let program = graphics.prgs.taa.bind();
+    program.uniform_texture(0, graphics.pass_final.outputs_get()[0].tex_get().raw_get());
+    program.uniform_texture(
+        1,
+        graphics.pass_history[prev].outputs_get()[0]
+            .tex_get()
+            .raw_get(),
+    );
+    program.uniform_texture(2, graphics.pass_motion.outputs_get()[0].tex_get().raw_get());
+    program.uniform_history_valid(graphics.history_valid);
+    graphics.fill_screen(program);
+    copy_color(&graphics.pass_history[next], &graphics.pass_final);
+
+    graphics.history_valid = true;
+    graphics.taa_trans.1 = current;
+    graphics.taa_jitter.1 = jitter;
+    graphics.object_trans.1 = std::mem::take(&mut graphics.object_trans.0);
+    graphics.taa_frame += 1;
+}
+
+/// Downsamples the bright part of `pass_final` through `pass_bloom`,
+/// then upsamples it back while accumulating each level, and adds it to `pass_final`.
+pub fn bloom(graphics: &super::Graphics, threshold: f32, levels: usize, intensity: f32) {
+    deferred_mode(true, false, false);
+    unsafe {
+        gl::Disable(gl::DEPTH_TEST);
+        gl::Disable(gl::MULTISAMPLE);
+        gl::Disable(gl::CULL_FACE);
+        gl::Disable(gl::STENCIL_TEST);
+        gl::Disable(gl::BLEND);
+    }
+    let final_tex = &graphics.pass_final.outputs_get()[0];
+
+    // Step1: Downsample, where only the first one takes the threshold
+    let program = graphics.prgs.bloom_down.bind();
+    for i in 0..levels {
+        let (src, threshold) = if i == 0 {
+            (final_tex, threshold)
+        } else {
+            (&graphics.pass_bloom[i - 1].outputs_get()[0], -1.0)
+        };
+        let dst = &graphics.pass_bloom[i];
+        dst.bind();
+        program.uniform_texture(0, src.tex_get().raw_get());
+        program.uniform_bloom(threshold, 1.0, &dst.outputs_get()[0].size_get());
+        graphics.fill_screen(program);
+    }
+
+    // Step2: Upsample and accumulate, where the last one goes to `pass_final`
+    unsafe {
+        gl::Enable(gl::BLEND);
+        gl::BlendEquation(gl::FUNC_ADD);
+        gl::BlendFunc(gl::ONE, gl::ONE);
+    }
+    let program = graphics.prgs.bloom_up.bind();
+    for i in (0..levels).rev() {
+        let (dst, weight, size) = if i == 0 {
+            (&graphics.pass_final, intensity, final_tex.size_get())
+        } else {
+            let dst = &graphics.pass_bloom[i - 1];
+            (dst, 1.0, dst.outputs_get()[0].size_get())
+        };
+        dst.bind();
+        program.uniform_texture(
+            0,
+            graphics.pass_bloom[i].outputs_get()[0].tex_get().raw_get(),
+        );
+        program.uniform_bloom(0.0, weight, &size);
+        graphics.fill_screen(program);
+    }
+    unsafe {
+        gl::Disable(gl::BLEND);
+    }
+}
+
+/// Maps `pass_final` in HDR into `pass_ldr`, adapting the exposure if asked.
+pub fn tone_map(graphics: &mut super::Graphics, params: &super::ToneMapParams) {
+    deferred_mode(true, false, false);
+    unsafe {
+        gl::Disable(gl::BLEND);
+        gl::Disable(gl::DEPTH_TEST);
+        gl::Disable(gl::MULTISAMPLE);
+        gl::Disable(gl::CULL_FACE);
+        gl::Disable(gl::STENCIL_TEST);
+    }
+    let hdr = graphics.pass_final.outputs_get()[0].tex_get().raw_get();
+
+    if params.auto_exposure {
+        // Step1: Log-luminance of the screen, averaged by the mipmap
+        graphics.pass_luminance.bind();
+        let program = graphics.prgs.luminance.bind();
+        program.uniform_texture(0, hdr);
+        graphics.fill_screen(program);
+        graphics.pass_luminance.outputs_get()[0].generate_mipmap();
+
+        // Step2: Adapt the previous luminance to it
+        let (prev, next) = if graphics.exposure_flip {
+            (1, 0)
+        } else {
+            (0, 1)
+        };
+        graphics.exposure_flip = !graphics.exposure_flip;
+        graphics.pass_adapted[next].bind();
+        let program = graphics.prgs.adapt_luminance.bind();
+        program.uniform_texture(
+            0,
+            graphics.pass_luminance.outputs_get()[0].tex_get().raw_get(),
+        );
+        program.uniform_texture(
+            1,
+            graphics.pass_adapted[prev].outputs_get()[0]
+                .tex_get()
+                .raw_get(),
+        );
+        program.uniform_adaptation_rate(params.adaptation_rate);
+        graphics.fill_screen(program);
+    }
+
+    // Step3: Tone mapping
+    graphics.pass_ldr.bind();
+    let adapted = if graphics.exposure_flip { 1 } else { 0 };
+    let program = graphics.prgs.tone_map.bind();
+    program.uniform_texture(0, hdr);
+    program.uniform_texture(
+        1,
+        graphics.pass_adapted[adapted].outputs_get()[0]
+            .tex_get()
+            .raw_get(),
+    );
+    program.uniform_tone_map(
+        params.operator,
+        params.exposure,
+        params.auto_exposure,
+        &params.exposure_range,
+    );
+    graphics.fill_screen(program);
+}
+
 pub fn render_final(graphics: &super::Graphics) {
     deferred_mode(true, false, false);
 
@@ -176,7 +838,7 @@ pub fn render_final(graphics: &super::Graphics) {
         gl::ActiveTexture(gl::TEXTURE0);
         gl::BindTexture(
             gl::TEXTURE_2D,
-            graphics.pass_final.outputs_get()[0].tex_get().raw_get(),
+            graphics.pass_ldr.outputs_get()[0].tex_get().raw_get(),
         );
         gl::Uniform1i(program.uloc_get_tex()[0], 0);
 
@@ -201,7 +863,7 @@ pub fn render_yuv(graphics: &super::Graphics) {
         gl::ActiveTexture(gl::TEXTURE0);
         gl::BindTexture(
             gl::TEXTURE_2D,
-            graphics.pass_final.outputs_get()[0].tex_get().raw_get(),
+            graphics.pass_ldr.outputs_get()[0].tex_get().raw_get(),
         );
         gl::Uniform1i(program.uloc_get_tex()[0], 0);
 
@@ -209,26 +871,26 @@ pub fn render_yuv(graphics: &super::Graphics) {
     }
 }
 
-pub fn bind_forward(_graphics: &super::Graphics) {
-    deferred_mode(true, true, false);
-    //graphics.pass_final.bind(false);
+/// The final pass has no depth buffer,
+/// so forward programs must test the depth against the G-buffer by themselves.
+pub fn bind_forward(graphics: &super::Graphics) {
+    deferred_mode(true, false, false);
+    graphics.pass_final.bind();
     unsafe {
         gl::Enable(gl::BLEND);
-        gl::Enable(gl::DEPTH_TEST);
-        gl::DepthFunc(gl::LESS);
+        gl::Disable(gl::DEPTH_TEST);
         gl::Disable(gl::MULTISAMPLE);
         gl::Enable(gl::CULL_FACE);
         gl::CullFace(gl::BACK);
         gl::Disable(gl::STENCIL_TEST);
+        gl::BlendEquation(gl::FUNC_ADD);
         gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
-
-        gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
     }
 }
 
 pub fn bind_2d(graphics: &super::Graphics) {
     deferred_mode(true, false, false);
-    graphics.pass_final.bind();
+    graphics.pass_ldr.bind();
     unsafe {
         gl::Enable(gl::BLEND);
         gl::Disable(gl::DEPTH_TEST);
@@ -4,6 +4,31 @@ use std::sync::Arc;
 pub struct Programs {
     pub dr_1: Arc<Shader>,
     pub dr_2: Arc<Shader>,
+    pub dr_2_cube: Arc<Shader>,
+    pub dr_2_tiled: Arc<Shader>,
+    pub luminance: Arc<Shader>,
+    pub adapt_luminance: Arc<Shader>,
+    pub tone_map: Arc<Shader>,
+    pub bloom_down: Arc<Shader>,
+    pub bloom_up: Arc<Shader>,
+    pub ssao: Arc<Shader>,
+    pub ssao_blur: Arc<Shader>,
+    pub ssr: Arc<Shader>,
+    pub ssr_resolve: Arc<Shader>,
+    pub fxaa: Arc<Shader>,
+    pub motion: Arc<Shader>,
+    pub motion_object: Arc<Shader>,
+    pub taa: Arc<Shader>,
+    pub skybox: Arc<Shader>,
+    pub dr_2_env: Arc<Shader>,
+    pub dr_2_ibl: Arc<Shader>,
+    pub prefilter: Arc<Shader>,
+    pub brdf_lut: Arc<Shader>,
+    pub dr_2_reflection: Arc<Shader>,
+    pub fog_medium: Arc<Shader>,
+    pub fog_light: Arc<Shader>,
+    pub fog_integrate: Arc<Shader>,
+    pub fog_apply: Arc<Shader>,
     pub dr_2_ambient: Arc<Shader>,
     pub dr_2_irradiance: Arc<Shader>,
     pub dr_2_omni: Arc<Shader>,
@@ -17,8 +42,10 @@ pub struct Programs {
     pub basic: Arc<Shader>,
     pub standard: Arc<Shader>,
     pub standard_probe: Arc<Shader>,
+    pub standard_forward: Arc<Shader>,
 
     pub shadow_1: Arc<Shader>,
+    pub shadow_cube: Arc<Shader>,
     pub probe: Arc<Shader>,
 
     pub sh_visualize: Arc<Shader>,
@@ -36,6 +63,31 @@ impl Programs {
         Programs {
             dr_1: glmanager.get_program("dr_1"),
             dr_2: glmanager.get_program("dr_2"),
+            dr_2_cube: glmanager.get_program("dr_2_cube"),
+            dr_2_tiled: glmanager.get_program("dr_2_tiled"),
+            luminance: glmanager.get_program("luminance"),
+            adapt_luminance: glmanager.get_program("adapt_luminance"),
+            tone_map: glmanager.get_program("tone_map"),
+            bloom_down: glmanager.get_program("bloom_down"),
+            bloom_up: glmanager.get_program("bloom_up"),
+            ssao: glmanager.get_program("ssao"),
+            ssao_blur: glmanager.get_program("ssao_blur"),
+            ssr: glmanager.get_program("ssr"),
+            ssr_resolve: glmanager.get_program("ssr_resolve"),
+            fxaa: glmanager.get_program("fxaa"),
+            motion: glmanager.get_program("motion"),
+            motion_object: glmanager.get_program("motion_object"),
+            taa: glmanager.get_program("taa"),
+            skybox: glmanager.get_program("skybox"),
+            dr_2_env: glmanager.get_program("dr_2_env"),
+            dr_2_ibl: glmanager.get_program("dr_2_ibl"),
+            prefilter: glmanager.get_program("prefilter"),
+            brdf_lut: glmanager.get_program("brdf_lut"),
+            dr_2_reflection: glmanager.get_program("dr_2_reflection"),
+            fog_medium: glmanager.get_program("fog_medium"),
+            fog_light: glmanager.get_program("fog_light"),
+            fog_integrate: glmanager.get_program("fog_integrate"),
+            fog_apply: glmanager.get_program("fog_apply"),
             dr_2_ambient: glmanager.get_program("dr_2_ambient"),
             dr_2_irradiance: glmanager.get_program("dr_2_irradiance"),
             dr_2_omni: glmanager.get_program("dr_2_omni"),
@@ -49,8 +101,10 @@ impl Programs {
             basic: glmanager.get_program("basic"),
             standard: glmanager.get_program("standard"),
             standard_probe: glmanager.get_program("standard_probe"),
+            standard_forward: glmanager.get_program("standard_forward"),
 
             shadow_1: glmanager.get_program("shadow_1"),
+            shadow_cube: glmanager.get_program("shadow_cube"),
             probe: glmanager.get_program("probe"),
 
             sh_visualize: glmanager.get_program("sh_visualize"),
@@ -42,6 +42,41 @@ impl Graphics {
         gl::ReadPixels(0, 0, size.x, size.y, gl::RGB, gl::FLOAT, buffer.cast());
     }
 
+    /// Reads the world position and the normal stored in the G-buffer at the given pixel.
+    ///
+    /// The position must be inside the screen.
+    pub fn read_gbuffer_geometry(&self, pos: IVec2) -> (Vec3, Vec3) {
+        debug_assert!(
+            pos.x >= 0 && pos.y >= 0 && pos.x < self.screen_size.x && pos.y < self.screen_size.y
+        );
+        let mut wpos = Vec3::from_element(0.0);
+        let mut wnormal = Vec3::from_element(0.0);
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.pass_deferred1.raw_get());
+            gl::ReadBuffer(gl::COLOR_ATTACHMENT0);
+            gl::ReadPixels(
+                pos.x,
+                pos.y,
+                1,
+                1,
+                gl::RGB,
+                gl::FLOAT,
+                wpos.as_mut_ptr().cast(),
+            );
+            gl::ReadBuffer(gl::COLOR_ATTACHMENT1);
+            gl::ReadPixels(
+                pos.x,
+                pos.y,
+                1,
+                1,
+                gl::RGB,
+                gl::FLOAT,
+                wnormal.as_mut_ptr().cast(),
+            );
+        }
+        (wpos, wnormal)
+    }
+
     pub unsafe fn read_yuv(
         &self,
         buffer_y: *mut u8,
@@ -4,9 +4,14 @@ use gl::types::{GLenum, GLuint};
 pub enum InternalTexType {
     Float1,     //
     Float3,     // Position, Emission
+    Float4,     // Packed data
+    Luminance,  // Single float channel
+    HalfFloat2, // Motion vector
     HalfFloat3, // Normal
     Color,      // Base Color
     Index,      // Object Index
+    Int1,       // Packed indices
+    Int2,       // Packed (offset, count)
     Flag,       // Lighting
     Material,   // Roughness, Metalness
     Depth,
@@ -39,9 +44,14 @@ impl InternalTexType {
         TexParam::new(match self {
             InternalTexType::Float1 => (gl::RED, gl::RED, gl::FLOAT, 1, 4),
             InternalTexType::Float3 => (gl::RGB32F, gl::RGB, gl::FLOAT, 3, 4),
+            InternalTexType::Float4 => (gl::RGBA32F, gl::RGBA, gl::FLOAT, 4, 4),
+            InternalTexType::Luminance => (gl::R32F, gl::RED, gl::FLOAT, 1, 4),
+            InternalTexType::HalfFloat2 => (gl::RG16F, gl::RG, gl::HALF_FLOAT, 2, 2),
             InternalTexType::HalfFloat3 => (gl::RGB16F, gl::RGB, gl::HALF_FLOAT, 3, 2),
             InternalTexType::Color => (gl::RGB, gl::RGB, gl::UNSIGNED_BYTE, 3, 1),
             InternalTexType::Index => (gl::R32UI, gl::RED_INTEGER, gl::UNSIGNED_INT, 1, 4),
+            InternalTexType::Int1 => (gl::R32I, gl::RED_INTEGER, gl::INT, 1, 4),
+            InternalTexType::Int2 => (gl::RG32I, gl::RG_INTEGER, gl::INT, 2, 4),
             InternalTexType::Flag => (gl::R8I, gl::RED_INTEGER, gl::UNSIGNED_BYTE, 1, 1),
             InternalTexType::Material => (gl::RED, gl::RED, gl::UNSIGNED_BYTE, 1, 1),
             InternalTexType::Depth => (gl::DEPTH_COMPONENT, gl::DEPTH_COMPONENT, gl::FLOAT, 1, 4),
@@ -120,8 +130,32 @@ impl TextureInternal2D {
         }
     }
 
-    #[allow(dead_code)]
-    pub fn load(&self, buf: &[f32]) {
+    /// Uses bilinear filtering, for the textures sampled in different resolutions.
+    pub fn filter_linear(&self) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, self.tex.raw);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+        }
+    }
+
+    /// Fills all the mipmap levels from the base level.
+    pub fn generate_mipmap(&self) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, self.tex.raw);
+            gl::TexParameteri(
+                gl::TEXTURE_2D,
+                gl::TEXTURE_MIN_FILTER,
+                gl::NEAREST_MIPMAP_NEAREST as i32,
+            );
+            gl::GenerateMipmap(gl::TEXTURE_2D);
+        }
+    }
+
+    /// `T` must match the data type of the texture.
+    pub fn load<T>(&self, buf: &[T]) {
         assert!(buf.len() >= (self.size.x as usize) * (self.size.y as usize) * self.tex_param.num);
         unsafe {
             gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
@@ -218,6 +252,18 @@ impl TextureInternal3D {
         }
     }
 
+    /// Samples with the trilinear filter, clamped to the edge.
+    pub fn filter_linear(&self) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_3D, self.tex.raw);
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_WRAP_R, gl::CLAMP_TO_EDGE as i32);
+        }
+    }
+
     /// Does some shit
     ///
     /// # Safety
@@ -249,6 +295,179 @@ impl TextureInternal3D {
     }
 }
 
+pub struct TextureInternalCube {
+    tex: TextureInternal,
+    size: IVec2,
+}
+
+impl TextureInternalCube {
+    pub fn new(tex_type: InternalTexType, size: IVec2) -> Self {
+        let tex_param = tex_type.tex_parameters();
+        let mut tex = TextureInternal { raw: 0, tex_type };
+        unsafe {
+            gl::GenTextures(1, &mut tex.raw);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, tex.raw);
+            for face in 0..6 {
+                gl::TexImage2D(
+                    gl::TEXTURE_CUBE_MAP_POSITIVE_X + face,
+                    0,
+                    tex_param.internal_format as i32,
+                    size.x,
+                    size.y,
+                    0,
+                    tex_param.format,
+                    tex_param.data_type,
+                    std::ptr::null(),
+                );
+            }
+            Self::parameters_set();
+        }
+        TextureInternalCube { tex, size }
+    }
+
+    fn parameters_set() {
+        unsafe {
+            gl::TexParameteri(
+                gl::TEXTURE_CUBE_MAP,
+                gl::TEXTURE_MIN_FILTER,
+                gl::NEAREST as i32,
+            );
+            gl::TexParameteri(
+                gl::TEXTURE_CUBE_MAP,
+                gl::TEXTURE_MAG_FILTER,
+                gl::NEAREST as i32,
+            );
+            gl::TexParameteri(
+                gl::TEXTURE_CUBE_MAP,
+                gl::TEXTURE_WRAP_S,
+                gl::CLAMP_TO_EDGE as i32,
+            );
+            gl::TexParameteri(
+                gl::TEXTURE_CUBE_MAP,
+                gl::TEXTURE_WRAP_T,
+                gl::CLAMP_TO_EDGE as i32,
+            );
+            gl::TexParameteri(
+                gl::TEXTURE_CUBE_MAP,
+                gl::TEXTURE_WRAP_R,
+                gl::CLAMP_TO_EDGE as i32,
+            );
+        }
+    }
+
+    /// Allocates `levels` mipmap levels in total, to be rendered one by one,
+    /// and samples them with the trilinear filter.
+    pub fn allocate_mipmap(&self, levels: usize) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.tex.raw);
+            gl::TexParameteri(
+                gl::TEXTURE_CUBE_MAP,
+                gl::TEXTURE_MAX_LEVEL,
+                levels as i32 - 1,
+            );
+            gl::GenerateMipmap(gl::TEXTURE_CUBE_MAP);
+            gl::TexParameteri(
+                gl::TEXTURE_CUBE_MAP,
+                gl::TEXTURE_MIN_FILTER,
+                gl::LINEAR_MIPMAP_LINEAR as i32,
+            );
+            gl::TexParameteri(
+                gl::TEXTURE_CUBE_MAP,
+                gl::TEXTURE_MAG_FILTER,
+                gl::LINEAR as i32,
+            );
+        }
+    }
+
+    /// Fills the mipmap levels allocated by `allocate_mipmap()` from the base level.
+    pub fn generate_mipmap(&self) {
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.tex.raw);
+            gl::GenerateMipmap(gl::TEXTURE_CUBE_MAP);
+        }
+    }
+
+    pub fn tex_get(&self) -> &TextureInternal {
+        &self.tex
+    }
+
+    pub fn size_get(&self) -> IVec2 {
+        self.size
+    }
+}
+
+/// A depth-only framebuffer that renders to each face of a cube map.
+pub struct CubeFrameBuffer {
+    raw: GLuint,
+    depth: TextureInternalCube,
+}
+
+impl CubeFrameBuffer {
+    pub fn new(depth: TextureInternalCube) -> Self {
+        let mut raw = 0;
+        unsafe {
+            gl::GenFramebuffers(1, &mut raw);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, raw);
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_ATTACHMENT,
+                gl::TEXTURE_CUBE_MAP_POSITIVE_X,
+                depth.tex.raw,
+                0,
+            );
+            gl::DrawBuffer(gl::NONE);
+            gl::ReadBuffer(gl::NONE);
+            debug_assert_eq!(
+                gl::CheckFramebufferStatus(gl::FRAMEBUFFER),
+                gl::FRAMEBUFFER_COMPLETE,
+                "Failed to create a Framebuffer"
+            )
+        }
+        CubeFrameBuffer { raw, depth }
+    }
+
+    pub fn depth_get(&self) -> &TextureInternalCube {
+        &self.depth
+    }
+
+    pub fn raw_get(&self) -> GLuint {
+        self.raw
+    }
+
+    /// `face` follows the order of `GL_TEXTURE_CUBE_MAP_POSITIVE_X + face`
+    pub fn bind_face(&self, face: usize) {
+        debug_assert!(face < 6);
+        unsafe {
+            gl::Viewport(0, 0, self.depth.size.x, self.depth.size.y);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.raw);
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_ATTACHMENT,
+                gl::TEXTURE_CUBE_MAP_POSITIVE_X + face as GLenum,
+                self.depth.tex.raw,
+                0,
+            );
+        }
+    }
+
+    /// Call this after `bind_face()`
+    pub fn clear_depth(&self) {
+        unsafe {
+            gl::DepthMask(1);
+            gl::Clear(gl::DEPTH_BUFFER_BIT);
+        }
+    }
+}
+
+impl Drop for CubeFrameBuffer {
+    fn drop(&mut self) {
+        let buf = [self.raw];
+        unsafe {
+            gl::DeleteFramebuffers(1, buf.as_ptr().cast());
+        }
+    }
+}
+
 pub struct FrameBuffer {
     raw: GLuint,
     outputs: Vec<TextureInternal2D>,
@@ -350,8 +569,21 @@ impl FrameBuffer {
         unsafe {
             gl::ColorMask(0, 0, 0, 0);
             gl::ColorMaski(index as u32, 1, 1, 1, 1);
-            gl::ClearColor(0.0, 0.0, 0.0, 1.0);
-            gl::Clear(gl::COLOR_BUFFER_BIT);
+            // Integer attachments can't be cleared with a float color
+            match self.outputs[index].tex.tex_type {
+                InternalTexType::Index | InternalTexType::Yuv => {
+                    let zero: [u32; 4] = [0, 0, 0, 0];
+                    gl::ClearBufferuiv(gl::COLOR, index as i32, zero.as_ptr());
+                }
+                InternalTexType::Flag => {
+                    let zero: [i32; 4] = [0, 0, 0, 0];
+                    gl::ClearBufferiv(gl::COLOR, index as i32, zero.as_ptr());
+                }
+                _ => {
+                    gl::ClearColor(0.0, 0.0, 0.0, 1.0);
+                    gl::Clear(gl::COLOR_BUFFER_BIT);
+                }
+            }
         }
     }
 
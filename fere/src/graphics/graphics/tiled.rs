@@ -0,0 +1,56 @@
+use super::texture_internal::{InternalTexType, TextureInternal2D};
+use fere_common::*;
+
+/// The size of a screen-space tile in pixels. Keep this same as `TILE_SIZE` in `fdr2_tiled.glsl`.
+pub const TILE_SIZE: i32 = 16;
+
+/// The number of RGBA texels for a light: (pos, radius), (color, _), (attenuation, _)
+pub const LIGHT_TEXELS: usize = 3;
+
+/// The width of the texture of light indices. Keep this same as `LIST_WIDTH` in `fdr2_tiled.glsl`.
+pub const LIST_WIDTH: i32 = 4096;
+
+/// Point lights binned into screen-space tiles, uploaded as textures.
+pub struct TiledLights {
+    lights: TextureInternal2D,
+    grid: TextureInternal2D,
+    list: TextureInternal2D,
+}
+
+impl TiledLights {
+    /// - `lights`: `LIGHT_TEXELS` RGBA texels for each light.
+    /// - `grid`: (offset, count) in `list` for each tile, row by row.
+    /// - `list`: indices of the lights.
+    pub fn new(lights: &[f32], tiles: IVec2, grid: &[i32], list: &[i32]) -> Self {
+        debug_assert_eq!(grid.len(), (tiles.x * tiles.y * 2) as usize);
+
+        let light_num = (lights.len() / (LIGHT_TEXELS * 4)).max(1);
+        let mut light_buf = lights.to_vec();
+        light_buf.resize(light_num * LIGHT_TEXELS * 4, 0.0);
+        let lights = TextureInternal2D::new(
+            InternalTexType::Float4,
+            IVec2::new(LIGHT_TEXELS as i32, light_num as i32),
+        );
+        lights.load(&light_buf);
+
+        let grid_tex = TextureInternal2D::new(InternalTexType::Int2, tiles);
+        grid_tex.load(grid);
+
+        let rows = ((list.len() as i32 + LIST_WIDTH - 1) / LIST_WIDTH).max(1);
+        let mut list_buf = list.to_vec();
+        list_buf.resize((rows * LIST_WIDTH) as usize, 0);
+        let list = TextureInternal2D::new(InternalTexType::Int1, IVec2::new(LIST_WIDTH, rows));
+        list.load(&list_buf);
+
+        Self {
+            lights,
+            grid: grid_tex,
+            list,
+        }
+    }
+
+    /// (lights, grid, list)
+    pub fn textures(&self) -> [&TextureInternal2D; 3] {
+        [&self.lights, &self.grid, &self.list]
+    }
+}
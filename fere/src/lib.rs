@@ -6,17 +6,19 @@ mod renderer;
 pub mod resources;
 mod video_record;
 
-use crate::graphics::gi::probe_volume::ProbeVolumeSuite;
+use crate::graphics::gi::probe_volume::{ProbeScheduler, ProbeVolume, ProbeVolumeSuite};
+use crate::graphics::gi::reflection_probe::ReflectionProbe;
 use crate::graphics::graphics::{Graphics, GraphicsConfig};
-use configs::FereConfigs;
-use fere_common::geo::SixDir;
-use fere_common::vec::IteratorVec4;
+use configs::{FereConfigs, IrradianceVolumeConfigs};
+use fere_common::vec::{GridAccessor3, IteratorVec3};
 use fere_common::*;
 use frame::{Frame, FrameConfig};
 use frame::{OpQueueReceiver, OpQueueSender};
 use ops::ChamberIndex;
 use renderer::{RenderEnd, Renderer, RendererParams};
+use resources::{CubeTexture, EnvironmentMap};
 use std::cell::RefCell;
+use std::io::Write;
 use std::thread::ThreadId;
 use thiserror::Error;
 
@@ -28,10 +30,18 @@ mod included_files {
 }
 
 pub mod prelude {
-    pub use crate::configs::{FereConfigs, IrradianceVolumeConfigs};
-    pub use crate::frame::{Frame, FrameConfig};
+    pub use crate::configs::{
+        AntiAliasing, FereConfigs, IrradianceVolumeConfigs, PointLightShading, ProbeUpdatePriority,
+        SsaoConfigs, SsrConfigs,
+    };
+    pub use crate::frame::{
+        Bloom, Exposure, Frame, FrameClock, FrameConfig, ToneMapOperator, ToneMapping,
+    };
     pub use crate::resources::*;
-    pub use crate::{ops as rops, renderer::Renderer, ChamberConfig, Error, Fere};
+    pub use crate::{
+        ops as rops, renderer::Renderer, ChamberConfig, Error, FbLookUp, Fere,
+        ReflectionProbeConfig, ReflectionProbeUpdate,
+    };
     pub use fere_common::{self, *};
     pub use fere_resources;
 }
@@ -40,39 +50,76 @@ pub mod prelude {
 pub enum Error {
     #[error("Invalid chamber accesses")]
     InvalidChamberAccess,
+    #[error("Invalid reflection probe accesses")]
+    InvalidReflectionProbeAccess,
     #[error("Recording error: {0}")]
     RecordingError(String),
+    #[error("Video error: {0}")]
+    VideoError(String),
+    #[error("Probe volume error: {0}")]
+    ProbeVolumeError(String),
+    #[error("Invalid irradiance volume configs: {0}")]
+    InvalidIrradianceVolumeConfigs(String),
+}
+
+/// A result of looking up the G-buffer at a pixel.
+#[derive(Clone, Debug)]
+pub struct FbLookUp {
+    /// The `id` of the `Object` drawn on the pixel.
+    pub id: u64,
+    /// The world position
+    pub pos: Vec3,
+    /// The world normal
+    pub norm: Vec3,
 }
 
 #[derive(Clone, Debug)]
 pub struct ChamberConfig {
     pub bpos: Vec3,
     pub size: Vec3,
+    /// The path of a probe volume baked by `Fere::save_probe_volume()`,
+    /// so that the irradiance volume starts from it instead of from scratch.
+    ///
+    /// It must have been baked for the same chamber size, irradiance volume configs and `probe_resolution`.
+    pub probe_volume: Option<String>,
+    /// Overrides `FereConfigs::irradiance_volume` for this chamber, which must be enabled there.
+    /// `time_budget_ms` must be `None`, as it's shared by all chambers.
+    pub irradiance_volume: Option<IrradianceVolumeConfigs>,
 }
 
-pub struct ChamberState {
-    current_probe: (IVec3, SixDir),
-    probe_volume_suite: ProbeVolumeSuite,
+/// When a reflection probe is captured again, besides right after it's added.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReflectionProbeUpdate {
+    /// Only with `Fere::refresh_reflection_probe()`.
+    OnDemand,
+    /// Takes turns with the other round-robin probes of the chamber, one per frame.
+    RoundRobin,
 }
 
-impl ChamberState {
-    fn set_next_probe(&mut self) {
-        let num = self.probe_volume_suite.probe_volume().number();
-        let mut i = IteratorVec4 {
-            size: IVec4::new(6, num.x, num.y, num.z),
-            cur: IVec4::new(
-                self.current_probe.1 as i32,
-                self.current_probe.0.x,
-                self.current_probe.0.y,
-                self.current_probe.0.z,
-            ),
-        };
-        self.current_probe = if let Some(x) = i.next() {
-            (IVec3::new(x.y, x.z, x.w), x.x as i8)
-        } else {
-            (IVec3::new(0, 0, 0), 0)
-        };
-    }
+/// A placeable reflection probe of a chamber.
+///
+/// It reflects the emission of the static objects of the chamber.
+#[derive(Clone, Debug)]
+pub struct ReflectionProbeConfig {
+    /// Where the surroundings are captured from. It must be inside of the box.
+    pub pos: Vec3,
+    /// The box in world space which the captured surroundings are projected onto,
+    /// usually fitted to the walls of a room.
+    ///
+    /// Only the surfaces inside of it take the reflection, so the boxes of probes should not overlap.
+    pub box_min: Vec3,
+    pub box_max: Vec3,
+    pub update: ReflectionProbeUpdate,
+}
+
+pub struct ChamberState {
+    /// Of the chamber, or `FereConfigs`, or the default one if neither.
+    irradiance_volume: IrradianceVolumeConfigs,
+    probe_scheduler: ProbeScheduler,
+    probe_volume_suite: ProbeVolumeSuite,
+    reflection_probes: Vec<Option<ReflectionProbe>>,
+    /// The next index to look for a round-robin reflection probe.
+    next_reflection_probe: usize,
 }
 
 pub struct Chamber {
@@ -108,6 +155,8 @@ impl Fere {
                 shadow_resolution: configs.shadow_resolution,
                 probe_resolution: configs.probe_resolution,
                 max_major_lights: configs.max_major_lights,
+                sun_cascade_num: configs.sun_cascade_num,
+                sun_shadow_resolution: configs.sun_shadow_resolution,
                 video_record: configs.video_record,
             })),
             chambers: (0..configs.max_chamber_num).map(|_| None).collect(),
@@ -136,21 +185,163 @@ impl Fere {
             .iter()
             .position(|x| x.is_none())
             .ok_or(Error::InvalidChamberAccess)?;
-        let config_ = config.clone();
+        let irradiance_volume = match (&config.irradiance_volume, &self.configs.irradiance_volume) {
+            (Some(_), None) => {
+                return Err(Error::InvalidIrradianceVolumeConfigs(
+                    "It can't be overridden while `FereConfigs::irradiance_volume` is disabled"
+                        .to_owned(),
+                ))
+            }
+            (Some(x), Some(_)) => {
+                x.validate_override(&config.size)
+                    .map_err(Error::InvalidIrradianceVolumeConfigs)?;
+                x.clone()
+            }
+            (None, x) => {
+                let x = x.clone().unwrap_or_default();
+                x.validate(&config.size)
+                    .map_err(Error::InvalidIrradianceVolumeConfigs)?;
+                x
+            }
+        };
+        let probe_volume_suite = if let Some(path) = &config.probe_volume {
+            ProbeVolumeSuite::with_probe_volume(self.load_probe_volume(
+                path,
+                &config,
+                &irradiance_volume,
+            )?)
+        } else {
+            ProbeVolumeSuite::new(
+                config.size,
+                &irradiance_volume,
+                self.configs.probe_resolution,
+            )
+        };
         self.chambers[index] = Some(Chamber {
             config,
             state: ChamberState {
-                current_probe: (IVec3::new(0, 0, 0), 0),
-                probe_volume_suite: ProbeVolumeSuite::new(
-                    config_.size,
-                    self.configs.pv_scale,
-                    self.configs.probe_resolution,
-                ),
+                irradiance_volume,
+                probe_scheduler: ProbeScheduler::new(probe_volume_suite.probe_volume().number()),
+                probe_volume_suite,
+                reflection_probes: Vec::new(),
+                next_reflection_probe: 0,
             },
         });
         Ok(index as ChamberIndex)
     }
 
+    fn load_probe_volume(
+        &self,
+        path: &str,
+        config: &ChamberConfig,
+        irradiance_volume: &IrradianceVolumeConfigs,
+    ) -> Result<ProbeVolume, Error> {
+        let error = |e: String| Error::ProbeVolumeError(format!("{}: {}", path, e));
+        let file = std::fs::File::open(path).map_err(|e| error(e.to_string()))?;
+        let probe_volume = ProbeVolume::read(&mut std::io::BufReader::new(file))
+            .map_err(|e| error(e.to_string()))?;
+        if probe_volume.room_size() != config.size
+            || probe_volume.params() != irradiance_volume.sh_params
+            || probe_volume.min_gap() != irradiance_volume.wall_margin
+            || probe_volume.spacing() != irradiance_volume.spacing
+            || probe_volume.near() != irradiance_volume.near
+            || probe_volume.far() != irradiance_volume.far
+            || probe_volume.fb_size() != self.configs.probe_resolution
+        {
+            return Err(error(
+                "Baked for a different chamber size, irradiance volume configs or `probe_resolution`"
+                    .to_owned(),
+            ));
+        }
+        Ok(probe_volume)
+    }
+
+    /// The number of probes of a chamber along each axis.
+    ///
+    /// A probe is fully updated after all of its 6 faces are rendered, one per frame.
+    pub fn probe_number(&self, chamber_index: ChamberIndex) -> Result<IVec3, Error> {
+        Ok(self
+            .chambers
+            .get(chamber_index as usize)
+            .and_then(|x| x.as_ref())
+            .ok_or(Error::InvalidChamberAccess)?
+            .state
+            .probe_volume_suite
+            .probe_volume()
+            .number())
+    }
+
+    /// The number of probes of a chamber updated so far, since it's added.
+    pub fn probe_update_count(&self, chamber_index: ChamberIndex) -> Result<u64, Error> {
+        Ok(self
+            .chambers
+            .get(chamber_index as usize)
+            .and_then(|x| x.as_ref())
+            .ok_or(Error::InvalidChamberAccess)?
+            .state
+            .probe_scheduler
+            .update_count())
+    }
+
+    /// The SH coefficients of every probe of a chamber, flattened in the order of the probes.
+    ///
+    /// Each probe has diffuse (rgb), illumination (rgb) and depth for each parameter.
+    pub fn probe_coefficients(&self, chamber_index: ChamberIndex) -> Result<Vec<f32>, Error> {
+        let probe_volume = self
+            .chambers
+            .get(chamber_index as usize)
+            .and_then(|x| x.as_ref())
+            .ok_or(Error::InvalidChamberAccess)?
+            .state
+            .probe_volume_suite
+            .probe_volume();
+        let mut result = Vec::new();
+        for probe in probe_volume.probes() {
+            for (diffuse, illumination, depth) in &probe.sh {
+                result.extend(diffuse.iter().chain(illumination.iter()));
+                result.push(*depth);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Makes the irradiance probes of a chamber inside the given box updated before the others,
+    /// e.g. after the emissive objects around there have changed.
+    pub fn mark_probes_dirty(
+        &mut self,
+        chamber_index: ChamberIndex,
+        box_min: Vec3,
+        box_max: Vec3,
+    ) -> Result<(), Error> {
+        let state = &mut self.chamber_mut(chamber_index)?.state;
+        let probe_volume = state.probe_volume_suite.probe_volume();
+        for i in IteratorVec3::new(probe_volume.number()) {
+            let pos = probe_volume.probes()[GridAccessor3(probe_volume.number()).get(&i)].pos;
+            if (0..3).all(|d| box_min[d] <= pos[d] && pos[d] <= box_max[d]) {
+                state.probe_scheduler.mark_dirty(probe_volume, i);
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the current probe volume of a chamber to a file, to be loaded by `ChamberConfig::probe_volume`.
+    pub fn save_probe_volume(&self, chamber_index: ChamberIndex, path: &str) -> Result<(), Error> {
+        let chamber = self
+            .chambers
+            .get(chamber_index as usize)
+            .and_then(|x| x.as_ref())
+            .ok_or(Error::InvalidChamberAccess)?;
+        let error = |e: std::io::Error| Error::ProbeVolumeError(format!("{}: {}", path, e));
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path).map_err(error)?);
+        chamber
+            .state
+            .probe_volume_suite
+            .probe_volume()
+            .write(&mut file)
+            .map_err(error)?;
+        file.flush().map_err(error)
+    }
+
     /// Remove an existing chamber.
     ///
     /// Panics if there is no such chamber corresponding to the given index.
@@ -165,8 +356,103 @@ impl Fere {
             .unwrap()
     }
 
-    pub fn fetch_index_buffer(_pos: IVec2) -> u64 {
-        0
+    /// Adds a reflection probe to a chamber, which is captured in the next frame.
+    ///
+    /// Call this in the main thread, outside of frames.
+    pub fn add_reflection_probe(
+        &mut self,
+        chamber_index: ChamberIndex,
+        config: ReflectionProbeConfig,
+    ) -> Result<usize, Error> {
+        assert!(
+            is_main_thread(),
+            "This operation must be done in the main thread"
+        );
+        let probe = self.graphics().create_reflection_probe(config);
+        let probes = &mut self.chamber_mut(chamber_index)?.state.reflection_probes;
+        let index = probes.iter().position(|x| x.is_none()).unwrap_or_else(|| {
+            probes.push(None);
+            probes.len() - 1
+        });
+        probes[index] = Some(probe);
+        Ok(index)
+    }
+
+    /// Removes an existing reflection probe of a chamber.
+    ///
+    /// Call this in the main thread, outside of frames.
+    pub fn remove_reflection_probe(
+        &mut self,
+        chamber_index: ChamberIndex,
+        index: usize,
+    ) -> Result<(), Error> {
+        assert!(
+            is_main_thread(),
+            "This operation must be done in the main thread"
+        );
+        self.chamber_mut(chamber_index)?
+            .state
+            .reflection_probes
+            .get_mut(index)
+            .and_then(|x| x.take())
+            .ok_or(Error::InvalidReflectionProbeAccess)
+            .map(|_| ())
+    }
+
+    /// Makes a reflection probe captured again in the next frame.
+    pub fn refresh_reflection_probe(
+        &mut self,
+        chamber_index: ChamberIndex,
+        index: usize,
+    ) -> Result<(), Error> {
+        self.chamber_mut(chamber_index)?
+            .state
+            .reflection_probes
+            .get_mut(index)
+            .and_then(|x| x.as_mut())
+            .ok_or(Error::InvalidReflectionProbeAccess)?
+            .set_dirty(true);
+        Ok(())
+    }
+
+    fn chamber_mut(&mut self, index: ChamberIndex) -> Result<&mut Chamber, Error> {
+        self.chambers
+            .get_mut(index as usize)
+            .and_then(|x| x.as_mut())
+            .ok_or(Error::InvalidChamberAccess)
+    }
+
+    /// Reads the object index buffer of the last rendered frame.
+    ///
+    /// `pos` is given in pixel, from the left-bottom of the screen.
+    /// Returns `0` if there is no object at the given pixel.
+    pub fn fetch_index_buffer(&self, pos: IVec2) -> u64 {
+        self.graphics().get_object_index_fetcher().fetch(pos)
+    }
+
+    /// Looks up the object, the world position and the normal under the given pixel
+    /// of the last rendered frame.
+    ///
+    /// Returns `None` if there is no object there.
+    /// Call this between `end_frame()` and the next `new_frame()`.
+    pub fn look_up(&self, pos: IVec2) -> Option<FbLookUp> {
+        let id = self.fetch_index_buffer(pos);
+        if id == 0 {
+            return None;
+        }
+        let (pos, norm) = self.graphics().read_gbuffer_geometry(pos);
+        Some(FbLookUp { id, pos, norm })
+    }
+
+    /// Prepares the image-based lighting of an environment cube map, for `AddEnvironmentLight`.
+    ///
+    /// Call this in the main thread, outside of frames.
+    pub fn create_environment_map(&self, texture: &CubeTexture) -> EnvironmentMap {
+        assert!(
+            is_main_thread(),
+            "This operation must be done in the main thread"
+        );
+        self.graphics().create_environment_map(texture)
     }
 
     pub fn new_frame(&mut self, config: FrameConfig) -> (Frame, Renderer) {
@@ -233,5 +519,21 @@ fn create_renderer_param(configs: &FereConfigs, frame_configs: &FrameConfig) ->
         debug_lightvolume_outline: frame_configs.show_lightvolume_outline,
         enable_shadow: true,
         enable_irradiance_volume: configs.irradiance_volume.is_some(),
+        probe_time_budget_ms: configs
+            .irradiance_volume
+            .as_ref()
+            .and_then(|x| x.time_budget_ms),
+        enable_ssao: configs.ssao.is_some(),
+        ssao_strength: configs.ssao.as_ref().map_or(0.0, |x| x.strength),
+        ssao_radius: configs.ssao.as_ref().map_or(0.0, |x| x.radius),
+        enable_ssr: configs.ssr.is_some(),
+        ssr_max_steps: configs.ssr.as_ref().map_or(0, |x| x.max_steps),
+        ssr_max_distance: configs.ssr.as_ref().map_or(0.0, |x| x.max_distance),
+        ssr_thickness: configs.ssr.as_ref().map_or(0.0, |x| x.thickness),
+        point_light_shading: configs.point_light_shading,
+        anti_aliasing: configs.anti_aliasing,
+        tone_mapping: frame_configs.tone_mapping,
+        bloom: frame_configs.bloom,
+        emission_time: frame_configs.clock.phase(),
     }
 }
@@ -36,6 +36,15 @@ pub struct Object {
 
     /// The index of the chamber this object belongs to
     pub chamber_index: ChamberIndex,
+
+    /// An user-assigned identifier which will be written in the object index buffer.
+    ///
+    /// You can read it back with `Fere::fetch_index_buffer()` after the frame ends.
+    /// `0` is reserved for the empty space.
+    ///
+    /// It also tracks the object between frames for the motion vectors of TAA,
+    /// so give a unique one to each moving object. Objects with `0` are regarded as static.
+    pub id: u32,
 }
 
 #[derive(Debug)]
@@ -66,11 +75,35 @@ pub struct DrawEmissiveDynamic {
     /// The object
     pub object: Object,
 
-    /// The material sources used to represent the surface
-    pub materials: surface::EmissiveMaterialI,
+    /// The non-emissive part of the surface.
+    pub general: surface::GeneralI,
+
+    /// The material sources used to represent the surface, indexed by `CurrentEmission::Material`.
+    pub materials: Vec<surface::EmissiveMaterialI>,
 
     /// The surface to apply on the mesh.
     pub surface: surface::EmissiveDynamic,
+
+    /// Whether to put additional point light to approximate the emission. Use (0, 1].
+    ///
+    /// It uses the average position and color for the given object and the current emission,
+    /// so that a screen playing a video lights the room with the frame on it.
+    pub point_light: Option<f32>,
+}
+
+/// Draws a transparent object in the forward pass, after the deferred shading.
+///
+/// Transparent objects are sorted back-to-front by the distance from the camera,
+/// and lit by every light of the chamber but without shadows, up to 16 of the major, sun, omni and point lights.
+/// The environment lights and the sky light them as well, though only the first environment light is reflected,
+/// and neither the reflection probes nor the irradiance volume take part.
+#[derive(Debug)]
+pub struct DrawTransparent {
+    /// The object
+    pub object: Object,
+
+    /// The surface to apply on the mesh.
+    pub surface: surface::TransparentI,
 }
 
 #[derive(Debug)]
@@ -111,12 +144,83 @@ pub struct AddMajorLight {
     /// Camera perspective in radian.
     pub perspective: f32,
 
+    /// How the shadow is looked up.
+    pub shadow: ShadowParams,
+
+    /// How the light falls off, which also decides its range.
+    pub attenuation: Attenuation,
+
     /// The index of the chamber this light belongs to
     pub chamber_index: ChamberIndex,
 }
 
+/// How the intensity of a light falls off with the distance `d`,
+/// as `color / (constant + linear * d + quadratic * d^2)`.
+#[derive(Debug, Clone, Copy)]
+pub struct Attenuation {
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+
+    /// The intensity below which the light is ignored.
+    ///
+    /// The light volume is fitted to where the brightest channel reaches this,
+    /// and the light smoothly fades to zero there.
+    pub cutoff: f32,
+}
+
+impl Default for Attenuation {
+    fn default() -> Self {
+        Self {
+            constant: 0.0,
+            linear: 0.0,
+            quadratic: 0.2,
+            cutoff: 0.02,
+        }
+    }
+}
+
+/// How the shadow map of a major light is filtered.
+#[derive(Debug, Clone, Copy)]
+pub enum ShadowFilter {
+    /// A single lookup, which gives hard edges.
+    None,
+
+    /// Percentage-closer filtering with a fixed 5x5 kernel.
+    Pcf,
+
+    /// Percentage-closer soft shadows, where the penumbra widens as the receiver gets farther from the blocker.
+    Pcss {
+        /// The size of the light, in the texture space of the shadow map (1.0 covers the whole map).
+        light_size: f32,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ShadowParams {
+    pub filter: ShadowFilter,
+
+    /// A constant bias in the depth of the shadow map, to remove shadow acne.
+    pub bias: f32,
+
+    /// The distance to push the receiver along its normal before the lookup, in world space.
+    pub normal_offset: f32,
+}
+
+impl Default for ShadowParams {
+    fn default() -> Self {
+        Self {
+            filter: ShadowFilter::Pcf,
+            bias: 0.0001,
+            normal_offset: 0.0,
+        }
+    }
+}
+
+/// A major light which is omni-directional.
+///
+/// It casts shadows with a single cube shadow map, and is shaded with a sphere light volume.
 #[derive(Debug)]
-/// A major light which is omni-directional
 pub struct MajorLightOmni {
     /// The position of the light.
     pub pos: Vec3,
@@ -124,29 +228,12 @@ pub struct MajorLightOmni {
     /// The color of the light.
     pub color: Vec3,
 
+    /// How the light falls off, which also decides its range.
+    pub attenuation: Attenuation,
+
     /// The index of the chamber this light belongs to
     pub chamber_index: ChamberIndex,
 }
-impl From<MajorLightOmni> for RenderOp {
-    fn from(x: MajorLightOmni) -> Self {
-        RenderOp::Multiple(
-            (0..6)
-                .map(|i| {
-                    let (xdir, ydir) = fere_common::geo::six_sides_dir(i);
-                    AddMajorLight {
-                        pos: x.pos,
-                        color: x.color,
-                        xdir,
-                        ydir,
-                        perspective: (90.0_f32).to_radians(),
-                        chamber_index: x.chamber_index,
-                    }
-                    .into()
-                })
-                .collect(),
-        )
-    }
-}
 
 #[derive(Debug)]
 /// A plain poing light
@@ -157,6 +244,28 @@ pub struct AddPointLight {
     /// The color of the light.
     pub color: Vec3,
 
+    /// How the light falls off, which also decides its range.
+    pub attenuation: Attenuation,
+
+    /// The index of the chamber this light belongs to
+    pub chamber_index: ChamberIndex,
+}
+
+/// An infinitely distant directional light, such as the sun.
+///
+/// It casts shadows with cascaded shadow maps fitted to the camera frustum,
+/// and lights the whole chamber it belongs to.
+#[derive(Debug)]
+pub struct AddSunLight {
+    /// The direction the light travels to.
+    pub dir: Vec3,
+
+    /// The color of the light. Unlike the other lights, it doesn't attenuate.
+    pub color: Vec3,
+
+    /// How the shadow is looked up, in every cascade.
+    pub shadow: ShadowParams,
+
     /// The index of the chamber this light belongs to
     pub chamber_index: ChamberIndex,
 }
@@ -173,6 +282,19 @@ pub struct AddAmbientLight {
     pub chamber_index: ChamberIndex,
 }
 
+/// Lights the chamber with an environment map from `Fere::create_environment_map()`;
+/// the diffuse irradiance and the specular reflection by the roughness and the metalness of each surface.
+#[derive(Debug)]
+pub struct AddEnvironmentLight {
+    pub map: Arc<EnvironmentMap>,
+
+    /// A multiplier of the light.
+    pub intensity: f32,
+
+    /// The index of the chamber this light belongs to
+    pub chamber_index: ChamberIndex,
+}
+
 /// Shades a chamber with irradiance volume. Use only once for a chamber.
 #[derive(Debug)]
 pub struct ShadeWithIv {
@@ -183,6 +305,40 @@ pub struct ShadeWithIv {
     pub weight: f32,
 }
 
+/// Sets a cube map as the sky, which is drawn behind everything and can light the chambers.
+#[derive(Debug)]
+pub struct SetSkybox {
+    pub texture: Arc<CubeTexture>,
+
+    /// A multiplier of the color of the sky.
+    pub intensity: f32,
+
+    /// The intensity as an ambient light coming from the surrounding sky. `0.0` to disable.
+    pub ambient: f32,
+
+    /// `None` to set the background of the frame, which also lights every chamber.
+    ///
+    /// `Some` to override the environment lighting of the chamber only.
+    pub chamber_index: Option<ChamberIndex>,
+}
+
+/// Fills a chamber with fog, which scatters the light of the chamber and its major lights.
+///
+/// The fog is bounded by the chamber, and lit by the major lights through their shadow maps.
+#[derive(Debug)]
+pub struct SetFog {
+    /// The extinction per unit length at the bottom of the chamber.
+    pub density: f32,
+
+    /// How fast the density decays along +z from the bottom of the chamber. `0.0` for a uniform fog.
+    pub height_falloff: f32,
+
+    /// The ratio of the scattering to the extinction, for each channel.
+    pub albedo: Vec3,
+
+    pub chamber_index: ChamberIndex,
+}
+
 #[derive(Debug)]
 pub struct DrawImage {
     pub texture: Arc<Texture>,
@@ -213,8 +369,28 @@ pub struct DrawBillboard {
     pub color: Vec4,
 }
 
+/// Draws a `MutableTexture` as `DrawImage` does, after uploading its written pixels.
+#[derive(Debug)]
+pub struct DrawMutableImage {
+    pub texture: Arc<MutableTexture>,
+
+    /// Of the center of the image, from the center of the screen as (0, 0).
+    pub pos: Vec2,
+    /// In scale.
+    pub size: Vec2,
+    pub rotation: f32,
+
+    pub blend_mode: (),
+    pub color: Vec4,
+}
+
+/// Uploads the written pixels of a `MutableTexture`.
+///
+/// Push this before the objects whose surfaces use `MutableTexture::texture()`.
 #[derive(Debug)]
-pub struct DrawMutableImage {}
+pub struct UpdateMutableTexture {
+    pub texture: Arc<MutableTexture>,
+}
 
 #[derive(Debug)]
 pub struct VisualizeProbes {
@@ -244,6 +420,8 @@ pub enum RenderOp {
 
     // Special operations to configure the chamber
     SetCamera(SetCamera),
+    SetSkybox(SetSkybox),
+    SetFog(SetFog),
 
     // Draw various objects
     DrawLine(DrawLine),
@@ -251,11 +429,15 @@ pub enum RenderOp {
     DrawGeneral(DrawGeneral),
     DrawEmissiveStatic(DrawEmissiveStatic),
     DrawEmissiveDynamic(DrawEmissiveDynamic),
+    DrawTransparent(DrawTransparent),
 
     // Add various lights
     AddMajorLight(AddMajorLight),
+    MajorLightOmni(MajorLightOmni),
     AddPointLight(AddPointLight),
+    AddSunLight(AddSunLight),
     AddAmbientLight(AddAmbientLight),
+    AddEnvironmentLight(AddEnvironmentLight),
 
     // Perform global illumination
     ShadeWithIv(ShadeWithIv),
@@ -264,6 +446,7 @@ pub enum RenderOp {
     DrawImage(DrawImage),
     DrawBillboard(DrawBillboard),
     DrawMutableImage(DrawMutableImage),
+    UpdateMutableTexture(UpdateMutableTexture),
 
     // Various debugging tools
     VisualizeProbes(VisualizeProbes),
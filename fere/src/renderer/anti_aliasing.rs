@@ -0,0 +1,19 @@
+use super::*;
+
+impl RenderContext {
+    /// Records an object drawn in the G-buffer, for its motion vectors in TAA.
+    pub(super) fn record_motion(&mut self, object: &Object) {
+        if self.params.anti_aliasing == AntiAliasing::Taa {
+            self.graphics
+                .record_motion(Arc::clone(&object.mesh), object.id, &object.trans);
+        }
+    }
+
+    pub(super) fn anti_alias(&mut self) {
+        match self.params.anti_aliasing {
+            AntiAliasing::None => (),
+            AntiAliasing::Fxaa => self.graphics.fxaa(),
+            AntiAliasing::Taa => self.graphics.taa(),
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use super::*;
+use crate::graphics::graphics::MAX_BLOOM_LEVELS;
+
+impl RenderContext {
+    pub(super) fn bloom(&mut self) {
+        if let Some(Bloom {
+            threshold,
+            levels,
+            intensity,
+        }) = self.params.bloom
+        {
+            if levels > MAX_BLOOM_LEVELS {
+                self.logs.push(FrameLog::new(format!(
+                    "Bloom levels {} exceeds the maximum {}",
+                    levels, MAX_BLOOM_LEVELS
+                )));
+            }
+            let levels = levels.max(1).min(MAX_BLOOM_LEVELS);
+            self.graphics.bloom(threshold, levels, intensity);
+        }
+    }
+}
@@ -17,13 +17,18 @@ pub(crate) struct ChamberContext {
     pub chamber: Chamber,
 
     pub major_lights: Vec<AddMajorLight>,
+    pub omni_lights: Vec<MajorLightOmni>,
     pub ambient_lights: Vec<AddAmbientLight>,
+    pub environment_lights: Vec<AddEnvironmentLight>,
     pub point_lights: Vec<AddPointLight>,
+    pub sun_lights: Vec<AddSunLight>,
 
     pub shadow_objects: Vec<ChamberShadowObject>,
     pub emissive_static_objects: Vec<ChamberEmissiveStaticObject>,
 
     pub shade_with_iv: Option<ShadeWithIv>,
+    pub skybox: Option<SetSkybox>,
+    pub fog: Option<SetFog>,
 }
 
 impl ChamberContext {
@@ -31,11 +36,21 @@ impl ChamberContext {
         Self {
             chamber,
             major_lights: Default::default(),
+            omni_lights: Default::default(),
             ambient_lights: Default::default(),
+            environment_lights: Default::default(),
             point_lights: Default::default(),
+            sun_lights: Default::default(),
             shadow_objects: Default::default(),
             emissive_static_objects: Default::default(),
             shade_with_iv: Default::default(),
+            skybox: Default::default(),
+            fog: Default::default(),
         }
     }
+
+    /// Both the spot and the omni-directional ones, which render shadow maps of their own.
+    pub fn major_light_num(&self) -> usize {
+        self.major_lights.len() + self.omni_lights.len()
+    }
 }
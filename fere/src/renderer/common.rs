@@ -1,7 +1,61 @@
 use super::*;
 
+/// (filter, light_size, bias, normal_offset) as they are passed to the shader
+fn shadow_uniforms(params: &ShadowParams) -> (i32, f32, f32, f32) {
+    let (filter, light_size) = match params.filter {
+        ShadowFilter::None => (0, 0.0),
+        ShadowFilter::Pcf => (1, 0.0),
+        ShadowFilter::Pcss { light_size } => (2, light_size),
+    };
+    (filter, light_size, params.bias, params.normal_offset)
+}
+
+/// The upper bound of a light's range, for lights that barely attenuate.
+const MAX_LIGHT_RADIUS: f32 = 2000.0;
+
+/// The distance where the brightest channel of the light falls to `attenuation.cutoff`.
+pub fn light_radius(color: &Vec3, attenuation: &Attenuation) -> f32 {
+    let Attenuation {
+        constant: c,
+        linear: l,
+        quadratic: q,
+        cutoff,
+    } = *attenuation;
+    // Solve `q * d^2 + l * d + c = color / cutoff`
+    let k = c - color.max() / cutoff.max(std::f32::EPSILON);
+    let d = if q > 0.0 {
+        (-l + (l * l - 4.0 * q * k).max(0.0).sqrt()) / (2.0 * q)
+    } else if l > 0.0 {
+        -k / l
+    } else {
+        MAX_LIGHT_RADIUS
+    };
+    d.max(0.0).min(MAX_LIGHT_RADIUS)
+}
+
+pub fn attenuation_vec(attenuation: &Attenuation) -> Vec3 {
+    Vec3::new(
+        attenuation.constant,
+        attenuation.linear,
+        attenuation.quadratic,
+    )
+}
+
+pub fn prepare_point_light(point_light: &AddPointLight, shadow: bool) -> LightUni {
+    LightUni {
+        light: Light {
+            pos: Vec4::new(point_light.pos.x, point_light.pos.y, point_light.pos.z, 1.0),
+            color: point_light.color,
+            shadow,
+            attenuation: attenuation_vec(&point_light.attenuation),
+        },
+        radius: light_radius(&point_light.color, &point_light.attenuation),
+    }
+}
+
 pub fn prepare_major_light(major_light: &AddMajorLight) -> (LightDir, SetCamera) {
     let dir = -normalize(&glm::cross(&major_light.xdir, &major_light.ydir));
+    let radius = light_radius(&major_light.color, &major_light.attenuation);
     let mut camera = SetCamera::new(
         major_light.pos,
         major_light.pos + dir,
@@ -9,25 +63,235 @@ pub fn prepare_major_light(major_light: &AddMajorLight) -> (LightDir, SetCamera)
         major_light.perspective,
         1.0,
         0.1,
-        1000.0,
+        radius.max(1.0),
     );
     camera.trans();
 
+    let (filter, light_size, bias, normal_offset) = shadow_uniforms(&major_light.shadow);
     (
         LightDir {
             light: Light {
                 pos: Vec4::new(major_light.pos.x, major_light.pos.y, major_light.pos.z, 1.0),
                 color: major_light.color,
                 shadow: true,
+                attenuation: attenuation_vec(&major_light.attenuation),
             },
-            radius: 500.0,
+            radius,
             xdir: major_light.xdir,
             ydir: major_light.ydir,
             angle: major_light.perspective,
             round: false,
             smoothnes: 0.5,
             trans: camera.projection_get() * camera.view_get(),
+            filter,
+            light_size,
+            bias,
+            normal_offset,
         },
         camera,
     )
 }
+
+/// (look, up) of each face, in the order of `GL_TEXTURE_CUBE_MAP_POSITIVE_X + i`
+const CUBE_FACES: [([f32; 3], [f32; 3]); 6] = [
+    ([1.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+    ([-1.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+    ([0.0, 1.0, 0.0], [0.0, 0.0, 1.0]),
+    ([0.0, -1.0, 0.0], [0.0, 0.0, -1.0]),
+    ([0.0, 0.0, 1.0], [0.0, -1.0, 0.0]),
+    ([0.0, 0.0, -1.0], [0.0, -1.0, 0.0]),
+];
+
+/// Cameras looking at each face of a cube map at `pos`, in the order of the GL cube map faces.
+pub fn cube_face_cameras(pos: Vec3, near: f32, far: f32) -> Vec<SetCamera> {
+    CUBE_FACES
+        .iter()
+        .map(|(look, up)| {
+            let mut camera = SetCamera::new(
+                pos,
+                pos + Vec3::from(*look),
+                Vec3::from(*up),
+                (90.0_f32).to_radians(),
+                1.0,
+                near,
+                far,
+            );
+            camera.trans();
+            camera
+        })
+        .collect()
+}
+
+/// The far plane of the cube shadow map of an omni-directional major light,
+/// which also normalizes the distance stored in it.
+pub fn omni_light_far(light: &LightUni) -> f32 {
+    light.radius.max(1.0)
+}
+
+pub fn prepare_omni_light(omni_light: &MajorLightOmni) -> (LightUni, Vec<SetCamera>) {
+    let light = LightUni {
+        light: Light {
+            pos: Vec4::new(omni_light.pos.x, omni_light.pos.y, omni_light.pos.z, 1.0),
+            color: omni_light.color,
+            shadow: true,
+            attenuation: attenuation_vec(&omni_light.attenuation),
+        },
+        radius: light_radius(&omni_light.color, &omni_light.attenuation),
+    };
+    let cameras = cube_face_cameras(omni_light.pos, 0.1, omni_light_far(&light));
+    (light, cameras)
+}
+
+/// A cascade of a sun light, covering the slice `[near, far)` of the camera frustum along its look direction.
+pub struct SunCascade {
+    pub light: LightDir,
+    pub near: f32,
+    pub far: f32,
+}
+
+/// Splits the camera frustum with the practical split scheme, and fits an orthographic shadow map to each slice.
+///
+/// Shadow casters may lie outside of the frustum, so each shadow map is pulled back to cover the whole chamber.
+/// Each shadow map moves by whole texels of `resolution`, so that the shadow edges don't shimmer as the camera moves.
+pub fn prepare_sun_light(
+    sun_light: &AddSunLight,
+    camera: &SetCamera,
+    chamber_size: &Vec3,
+    cascade_num: usize,
+    resolution: usize,
+) -> Vec<SunCascade> {
+    const SPLIT_LAMBDA: f32 = 0.5;
+
+    let dir = normalize(&sun_light.dir);
+    let look = normalize(&(camera.look - camera.pos));
+    let right = normalize(&glm::cross(&look, &camera.up));
+    let up = glm::cross(&right, &look);
+    let tan_y = (camera.perspective / 2.0).tan();
+    let tan_x = tan_y * camera.ratio;
+    let pullback = chamber_size.norm();
+    let (filter, light_size, bias, normal_offset) = shadow_uniforms(&sun_light.shadow);
+
+    let split = |i: usize| {
+        let r = i as f32 / cascade_num as f32;
+        let log = camera.near * (camera.far / camera.near).powf(r);
+        let uni = camera.near + (camera.far - camera.near) * r;
+        SPLIT_LAMBDA * log + (1.0 - SPLIT_LAMBDA) * uni
+    };
+    let light_up = if dir.y.abs() > 0.99 {
+        Vec3::new(1.0, 0.0, 0.0)
+    } else {
+        Vec3::new(0.0, 1.0, 0.0)
+    };
+    // The axes of the shadow map, as `glm::look_at()` makes them
+    let light_x = normalize(&glm::cross(&dir, &light_up));
+    let light_y = glm::cross(&light_x, &dir);
+
+    (0..cascade_num)
+        .map(|i| {
+            let (near, far) = (split(i), split(i + 1));
+            let corners = [near, far]
+                .iter()
+                .flat_map(|&d| {
+                    let center = camera.pos + look * d;
+                    let (x, y) = (right * (tan_x * d), up * (tan_y * d));
+                    vec![
+                        center + x + y,
+                        center + x - y,
+                        center - x + y,
+                        center - x - y,
+                    ]
+                })
+                .collect::<Vec<_>>();
+            let center = corners.iter().fold(Vec3::zeros(), |acc, x| acc + x) / 8.0;
+            // The radius doesn't change as the camera turns, except for the rounding errors
+            let radius = corners
+                .iter()
+                .map(|x| (x - center).norm())
+                .fold(0.0_f32, f32::max);
+            let radius = ((radius * 16.0).ceil() / 16.0).max(1.0 / 16.0);
+
+            let texel = 2.0 * radius / resolution.max(1) as f32;
+            let snap = |axis: &Vec3| {
+                let x = center.dot(axis);
+                (x / texel).floor() * texel - x
+            };
+            let center = center + light_x * snap(&light_x) + light_y * snap(&light_y);
+
+            let eye = center - dir * (radius + pullback);
+            let view = glm::look_at(&eye, &center, &light_up);
+            let projection = glm::ortho(
+                -radius,
+                radius,
+                -radius,
+                radius,
+                0.0,
+                2.0 * radius + pullback,
+            );
+
+            SunCascade {
+                light: LightDir {
+                    light: Light {
+                        pos: Vec4::new(-dir.x, -dir.y, -dir.z, 0.0),
+                        color: sun_light.color,
+                        shadow: true,
+                        attenuation: Vec3::new(1.0, 0.0, 0.0),
+                    },
+                    radius: 0.0,
+                    xdir: Vec3::zeros(),
+                    ydir: Vec3::zeros(),
+                    angle: 0.0,
+                    round: false,
+                    smoothnes: 0.0,
+                    trans: projection * view,
+                    filter,
+                    light_size,
+                    bias,
+                    normal_offset,
+                },
+                near,
+                far,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn light_radius_reaches_cutoff() {
+        let attenuation = Attenuation::default();
+        let color = Vec3::new(1.0, 0.5, 0.25);
+        let d = light_radius(&color, &attenuation);
+        assert!((d - 250.0_f32.sqrt()).abs() < 0.001);
+
+        // The brightest channel falls to the cutoff at the boundary
+        let intensity = color.max()
+            / (attenuation.constant + attenuation.linear * d + attenuation.quadratic * d * d);
+        assert!((intensity - attenuation.cutoff).abs() < 0.0001);
+    }
+
+    #[test]
+    fn light_radius_without_falloff() {
+        let attenuation = Attenuation {
+            constant: 1.0,
+            linear: 0.0,
+            quadratic: 0.0,
+            cutoff: 0.02,
+        };
+        assert_eq!(
+            light_radius(&Vec3::new(1.0, 1.0, 1.0), &attenuation),
+            MAX_LIGHT_RADIUS
+        );
+        // Too dim to be seen anywhere
+        let attenuation = Attenuation {
+            linear: 1.0,
+            ..attenuation
+        };
+        assert_eq!(
+            light_radius(&Vec3::new(0.01, 0.01, 0.01), &attenuation),
+            0.0
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use super::*;
+use crate::graphics::graphics::FogMedium;
+
+impl RenderContext {
+    fn has_fog(&self) -> bool {
+        self.chamber_contexts
+            .iter()
+            .flatten()
+            .any(|chamber| chamber.fog.is_some())
+    }
+
+    /// Empties the froxels before the chambers scatter the light in them.
+    pub(super) fn begin_fog(&mut self) {
+        if !self.has_fog() {
+            return;
+        }
+        let camera = self
+            .camera_info
+            .as_ref()
+            .expect("You must set camera first");
+        self.graphics.begin_fog(
+            &(camera.projection_get() * camera.view_get()),
+            &camera.pos,
+            camera.near,
+            camera.far,
+        );
+    }
+
+    /// Scatters the ambient lights and the major lights of the chamber in its fog.
+    ///
+    /// The shadow maps of `major_lights` must be drawn already.
+    pub(super) fn scatter_fog(
+        &self,
+        chamber: &ChamberContext,
+        major_lights: &[(LightDir, SetCamera)],
+    ) {
+        let fog = if let Some(x) = chamber.fog.as_ref() {
+            x
+        } else {
+            return;
+        };
+        let medium = FogMedium {
+            bpos: chamber.chamber.config.bpos,
+            size: chamber.chamber.config.size,
+            density: fog.density,
+            height_falloff: fog.height_falloff,
+            albedo: fog.albedo,
+        };
+        let ambient = chamber
+            .ambient_lights
+            .iter()
+            .fold(Vec3::zeros(), |sum, light| sum + light.color);
+        self.graphics.scatter_fog_medium(&medium, &ambient);
+        for (i, (light, _)) in major_lights.iter().enumerate() {
+            self.graphics.scatter_fog_light(&medium, light, i);
+        }
+        self.graphics.bind_deferred_pass2(false);
+    }
+
+    pub(super) fn apply_fog(&self) {
+        if self.has_fog() {
+            self.graphics.apply_fog();
+        }
+    }
+}
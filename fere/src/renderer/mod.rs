@@ -1,15 +1,30 @@
+mod anti_aliasing;
+mod bloom;
 mod chamber;
 mod common;
+mod fog;
 mod images;
 mod probe;
+mod reflection;
 mod render;
 mod shading;
+mod skybox;
+mod ssao;
+mod ssr;
+mod tiled;
+mod tone_map;
+mod transparent;
 
 use super::*;
-use crate::frame::OpQueueReceiver;
+use crate::configs::{AntiAliasing, PointLightShading};
+use crate::frame::{Bloom, Exposure, OpQueueReceiver, ToneMapOperator, ToneMapping};
 use crate::graphics::glmanager::light::{Light, LightDir, LightUni};
+use crate::graphics::glmanager::shader::Shader;
 use crate::graphics::graphics::{
-    material::{bind_emissive_static, bind_fixed_color, bind_general},
+    material::{
+        bind_emissive_dynamic, bind_emissive_static, bind_fixed_color, bind_general,
+        bind_transparent,
+    },
     Graphics,
 };
 use crate::graphics::render_unit::{Lighting, RenderUnit};
@@ -52,6 +67,8 @@ pub enum OpError {
     InvalidChamberAccess(ChamberIndex),
     #[error("Duplicated operations that must be unique: {0}")]
     InvalidShade(String),
+    #[error("Major lights on chamber #{0} exceeded the budget of {1}")]
+    MajorLightBudget(ChamberIndex, usize),
     #[error("{0}")]
     Other(String),
 }
@@ -62,10 +79,12 @@ struct RenderContext {
     _fere_configs: FereConfigs,
     logs: Vec<FrameLog>,
     camera_info: Option<SetCamera>,
+    skybox: Option<SetSkybox>,
     chamber_contexts: Vec<Option<ChamberContext>>,
 
     draw_images: Vec<DrawImage>,
     draw_billboarsd: Vec<DrawBillboard>,
+    draw_transparents: Vec<DrawTransparent>,
     show_internal_textures: Vec<ShowInternalTexture>,
 
     _starting_instant: std::time::Instant,
@@ -90,6 +109,15 @@ impl RenderContext {
             .as_ref()
             .ok_or(OpError::InvalidChamberAccess(chamber_index))
     }
+
+    /// Fails if the chamber can't take another major light, either spot or omni-directional.
+    fn check_major_light_budget(&self, chamber_index: ChamberIndex) -> Result<(), OpError> {
+        let max_major_lights = self.graphics.max_major_lights();
+        if self.get_chamber_ctx(chamber_index)?.major_light_num() >= max_major_lights {
+            return Err(OpError::MajorLightBudget(chamber_index, max_major_lights));
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, serde::Deserialize)]
@@ -97,6 +125,23 @@ pub struct RendererParams {
     pub debug_lightvolume_outline: bool,
     pub enable_shadow: bool,
     pub enable_irradiance_volume: bool,
+    pub probe_time_budget_ms: Option<f32>,
+    pub enable_ssao: bool,
+    pub ssao_strength: f32,
+    pub ssao_radius: f32,
+    pub enable_ssr: bool,
+    /// The number of steps to march each reflected ray.
+    pub ssr_max_steps: usize,
+    /// How far each reflected ray goes, in world space.
+    pub ssr_max_distance: f32,
+    /// How thick a surface in the depth buffer is assumed to be, in world space.
+    pub ssr_thickness: f32,
+    pub point_light_shading: PointLightShading,
+    pub anti_aliasing: AntiAliasing,
+    pub tone_mapping: ToneMapping,
+    pub bloom: Option<Bloom>,
+    /// The phase of the emissive timepoints, in [0, 1).
+    pub emission_time: f64,
 }
 
 impl Default for RendererParams {
@@ -104,7 +149,20 @@ impl Default for RendererParams {
         Self {
             debug_lightvolume_outline: true,
             enable_irradiance_volume: false,
+            probe_time_budget_ms: None,
             enable_shadow: false,
+            enable_ssao: false,
+            ssao_strength: 0.0,
+            ssao_radius: 0.0,
+            enable_ssr: false,
+            ssr_max_steps: 0,
+            ssr_max_distance: 0.0,
+            ssr_thickness: 0.0,
+            point_light_shading: PointLightShading::LightVolume,
+            anti_aliasing: AntiAliasing::None,
+            tone_mapping: Default::default(),
+            bloom: None,
+            emission_time: 0.0,
         }
     }
 }
@@ -150,6 +208,7 @@ impl Renderer {
             _fere_configs: self.fere_configs.clone(),
 
             camera_info: Default::default(),
+            skybox: Default::default(),
             logs: Default::default(),
             chamber_contexts: self
                 .chambers
@@ -158,6 +217,7 @@ impl Renderer {
                 .collect(),
             draw_images: Default::default(),
             draw_billboarsd: Default::default(),
+            draw_transparents: Default::default(),
             show_internal_textures: Default::default(),
 
             _starting_instant: std::time::Instant::now(),
@@ -170,21 +230,36 @@ impl Renderer {
             match self.object_queue.recv().unwrap() {
                 RenderOp::EndFrame(_) => {
                     ctx.graphics.bind_deferred_pass2(true);
-                    // FIXME: Update only one.
                     if self.params.enable_irradiance_volume {
-                        for i in 0..self.fere_configs.max_chamber_num {
-                            if ctx.chamber_contexts[i].is_some() {
-                                ctx.update_probe(i as u32);
-                            }
+                        ctx.update_probes(self.fere_configs.max_chamber_num);
+                    }
+                    for i in 0..self.fere_configs.max_chamber_num {
+                        if ctx.chamber_contexts[i].is_some() {
+                            ctx.update_reflection_probes(i as u32);
                         }
                     }
 
+                    ctx.ambient_occlusion();
+                    ctx.begin_fog();
                     for i in 0..self.fere_configs.max_chamber_num {
                         if ctx.chamber_contexts[i].is_some() {
                             ctx.shade(i as u32);
                         }
                     }
 
+                    ctx.draw_skybox();
+                    ctx.screen_space_reflections();
+                    for i in 0..self.fere_configs.max_chamber_num {
+                        if ctx.chamber_contexts[i].is_some() {
+                            ctx.reflect(i as u32);
+                        }
+                    }
+                    ctx.apply_fog();
+                    ctx.render_transparents();
+                    ctx.anti_alias();
+                    ctx.bloom();
+                    ctx.tone_map();
+
                     ctx.graphics.bind_2d();
                     ctx.render_images();
                     ctx.graphics.render_final();
@@ -1,7 +1,7 @@
 use super::*;
 
 impl RenderContext {
-    fn render_irradiance_world(&self, chamber_index: ChamberIndex) {
+    pub(super) fn render_irradiance_world(&self, chamber_index: ChamberIndex) {
         let chamber = self.chamber_contexts[chamber_index as usize]
             .as_ref()
             .unwrap();
@@ -15,23 +15,55 @@ impl RenderContext {
         };
         self.graphics.ru_set(prg, &runit);
         for object in &chamber.emissive_static_objects {
-            bind_emissive_static(prg, &object.surface, 0.0);
+            bind_emissive_static(prg, &object.surface, self.params.emission_time);
             prg.uniform_model(&object.trans, false);
             object.mesh.bind();
             object.mesh.draw();
         }
     }
 
+    /// Renders the probe faces of every chamber within the budget, taking turns between the chambers.
+    pub(super) fn update_probes(&mut self, max_chamber_num: usize) {
+        let faces_per_frame = |ctx: &Option<ChamberContext>| {
+            ctx.as_ref()
+                .map_or(0, |x| x.chamber.state.irradiance_volume.faces_per_frame)
+        };
+        let rounds = self
+            .chamber_contexts
+            .iter()
+            .map(faces_per_frame)
+            .max()
+            .unwrap_or(0);
+        let start = std::time::Instant::now();
+        for round in 0..rounds {
+            if let (Some(budget), true) = (self.params.probe_time_budget_ms, round > 0) {
+                if start.elapsed().as_secs_f32() * 1000.0 >= budget {
+                    return;
+                }
+            }
+            for i in 0..max_chamber_num {
+                if round < faces_per_frame(&self.chamber_contexts[i]) {
+                    self.update_probe(i as u32);
+                }
+            }
+        }
+    }
+
     pub fn update_probe(&mut self, chamber_index: ChamberIndex) {
         self.graphics.bind_probe();
 
+        let camera_pos = self.camera_info.as_ref().map_or(Vec3::zeros(), |x| x.pos);
         let chamber = self.chamber_contexts[chamber_index as usize]
-            .as_ref()
+            .as_mut()
             .unwrap();
-        let probe = chamber.chamber.state.current_probe;
-        let mut camera = chamber
-            .chamber
-            .state
+        let state = &mut chamber.chamber.state;
+        let priority = state.irradiance_volume.priority;
+        let probe = state.probe_scheduler.current(
+            state.probe_volume_suite.probe_volume(),
+            priority,
+            camera_pos,
+        );
+        let mut camera = state
             .probe_volume_suite
             .probe_volume()
             .camera(probe.0, probe.1);
@@ -44,20 +76,17 @@ impl RenderContext {
         let chamber = self.chamber_contexts[chamber_index as usize]
             .as_mut()
             .unwrap();
+        let state = &mut chamber.chamber.state;
         unsafe {
-            chamber
-                .chamber
-                .state
+            state
                 .probe_volume_suite
                 .write_buffer(&self.graphics, probe.1 as u8);
         }
-        if probe.1 == 5 {
-            chamber
-                .chamber
-                .state
-                .probe_volume_suite
-                .update_probe(probe.0);
+        if state
+            .probe_scheduler
+            .advance(state.probe_volume_suite.probe_volume())
+        {
+            state.probe_volume_suite.update_probe(probe.0);
         }
-        chamber.chamber.state.set_next_probe();
     }
 }
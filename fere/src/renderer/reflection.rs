@@ -0,0 +1,127 @@
+use super::*;
+
+impl RenderContext {
+    /// Captures the dirty reflection probes of the chamber, and the next one of the round-robin.
+    pub(super) fn update_reflection_probes(&mut self, chamber_index: ChamberIndex) {
+        let state = &mut self.chamber_contexts[chamber_index as usize]
+            .as_mut()
+            .unwrap()
+            .chamber
+            .state;
+        let num = state.reflection_probes.len();
+        let mut targets: Vec<usize> = (0..num)
+            .filter(|&i| {
+                state.reflection_probes[i]
+                    .as_ref()
+                    .map_or(false, |x| x.is_dirty())
+            })
+            .collect();
+        for k in 0..num {
+            let i = (state.next_reflection_probe + k) % num;
+            if state.reflection_probes[i]
+                .as_ref()
+                .map_or(false, |x| x.is_round_robin())
+            {
+                if !targets.contains(&i) {
+                    targets.push(i);
+                }
+                state.next_reflection_probe = (i + 1) % num;
+                break;
+            }
+        }
+
+        for &i in targets.iter() {
+            self.capture_reflection_probe(chamber_index, i);
+        }
+
+        let state = &mut self.chamber_contexts[chamber_index as usize]
+            .as_mut()
+            .unwrap()
+            .chamber
+            .state;
+        for i in targets {
+            state.reflection_probes[i]
+                .as_mut()
+                .unwrap()
+                .set_dirty(false);
+        }
+    }
+
+    /// Shades the environment maps and the reflection probes of the chamber,
+    /// whose specular fills where the screen-space reflections miss.
+    ///
+    /// It's after the screen-space reflections, which take the shaded scene without these.
+    pub(super) fn reflect(&self, chamber_index: ChamberIndex) {
+        let chamber = if let Ok(x) = self.get_chamber_ctx(chamber_index) {
+            x
+        } else {
+            return;
+        };
+        let camera = self
+            .camera_info
+            .as_ref()
+            .expect("You must set camera first");
+
+        self.graphics.bind_deferred_pass2(false);
+
+        // - Environment maps
+        let prg = self.graphics.prgs.dr_2_ibl.bind();
+        self.graphics.bind_gbuffer(prg, 0);
+        self.graphics.bind_ambient_occlusion(prg, 10);
+        self.graphics.bind_screen_space_reflections(prg, 11);
+        prg.uniform_camera(&camera.pos);
+        for light in &chamber.environment_lights {
+            self.graphics.bind_environment_map(prg, 8, &light.map);
+            prg.uniform_sh(light.map.irradiance());
+            prg.uniform_ibl(light.intensity, light.map.levels());
+            self.graphics.draw_lightvolume_ambient(
+                prg,
+                &(chamber.chamber.config.bpos - Vec3::new(0.5, 0.5, 0.5)),
+                &camera.pos,
+                &(chamber.chamber.config.size + Vec3::new(1.0, 1.0, 1.0)),
+            );
+        }
+
+        // - Reflection probes
+        let prg = self.graphics.prgs.dr_2_reflection.bind();
+        self.graphics.bind_gbuffer(prg, 0);
+        self.graphics.bind_ambient_occlusion(prg, 10);
+        self.graphics.bind_screen_space_reflections(prg, 11);
+        prg.uniform_camera(&camera.pos);
+        for probe in chamber.chamber.state.reflection_probes.iter().flatten() {
+            let config = probe.config();
+            self.graphics.bind_reflection_probe(prg, probe);
+            prg.uniform_reflection_probe(&config.pos, &config.box_min, &config.box_max);
+            prg.uniform_ibl(1.0, probe.levels());
+            self.graphics.draw_lightvolume_ambient(
+                prg,
+                &(config.box_min - Vec3::new(0.5, 0.5, 0.5)),
+                &camera.pos,
+                &(config.box_max - config.box_min + Vec3::new(1.0, 1.0, 1.0)),
+            );
+        }
+    }
+
+    fn capture_reflection_probe(&self, chamber_index: ChamberIndex, index: usize) {
+        let chamber = self.chamber_contexts[chamber_index as usize]
+            .as_ref()
+            .unwrap();
+        let probe = chamber.chamber.state.reflection_probes[index]
+            .as_ref()
+            .unwrap();
+        let config = probe.config();
+        // Far enough to see every corner of the box from anywhere inside of it
+        let far = glm::length(&(config.box_max - config.box_min)).max(1.0);
+        for (face, camera) in common::cube_face_cameras(config.pos, 0.1, far)
+            .iter()
+            .enumerate()
+        {
+            self.graphics.bind_probe();
+            let prg = self.graphics.prgs.standard_probe.bind();
+            prg.uniform_transformations(camera.projection_get(), camera.view_get());
+            self.render_irradiance_world(chamber_index);
+            self.graphics.capture_reflection_face(probe, face);
+        }
+        self.graphics.prefilter_reflection_probe(probe);
+    }
+}
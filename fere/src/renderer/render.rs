@@ -1,4 +1,5 @@
 use super::*;
+use crate::graphics::glmanager::shader::Uniform;
 use crate::prelude::*;
 use chamber::*;
 
@@ -12,18 +13,17 @@ impl RenderContext {
                 self.graphics.bind_deferred_pass1();
                 Ok(None)
             }
-            RenderOp::SetCamera(camera_info_) => {
-                let programs = vec![
-                    self.graphics.prgs.basic.as_ref(),
-                    self.graphics.prgs.standard.as_ref(),
-                    self.graphics.prgs.sh_visualize.as_ref(),
-                    self.graphics.prgs.sh_visualize_single.as_ref(),
-                    self.graphics.prgs.geo_visualize.as_ref(),
-                    self.graphics.prgs.dr_2.as_ref(),
-                    self.graphics.prgs.dr_2_irradiance.as_ref(),
-                    self.graphics.prgs.dr_2_ambient.as_ref(),
-                    self.graphics.prgs.dr_2_omni.as_ref(),
-                ];
+            RenderOp::SetCamera(mut camera_info_) => {
+                if self.params.anti_aliasing == AntiAliasing::Taa {
+                    self.graphics.jitter_camera(&mut camera_info_);
+                }
+                // Every program taking the transformations, so that a new one can't miss them.
+                // The ones drawing from other views set their own before drawing anyway.
+                let programs = self
+                    .graphics
+                    .get_gl()
+                    .programs()
+                    .filter(|x| x.uloc_get(Uniform::Projection) != -1);
                 for program in programs {
                     program.bind();
                     program.uniform_transformations(
@@ -34,6 +34,18 @@ impl RenderContext {
                 self.camera_info = Some(camera_info_);
                 Ok(None)
             }
+            RenderOp::SetSkybox(x) => {
+                if let Some(chamber_index) = x.chamber_index {
+                    self.get_mut_chamber_ctx(chamber_index)?.skybox = Some(x);
+                } else {
+                    self.skybox = Some(x);
+                }
+                Ok(None)
+            }
+            RenderOp::SetFog(x) => {
+                self.get_mut_chamber_ctx(x.chamber_index)?.fog = Some(x);
+                Ok(None)
+            }
             RenderOp::DrawLine(DrawLine {
                 pos1,
                 pos2,
@@ -66,7 +78,7 @@ impl RenderContext {
                     color: true,
                     depth: true,
                     depth_test: true,
-                    id: None,
+                    id: Some(object.id),
                     lighting: Some(Lighting::DefFull),
                 };
                 self.graphics.ru_set(prg, &runit);
@@ -76,6 +88,7 @@ impl RenderContext {
 
                 bind_general(prg, &surface);
                 object.mesh.draw();
+                self.record_motion(&object);
 
                 if object.shadow {
                     self.get_mut_chamber_ctx(object.chamber_index)?
@@ -100,15 +113,16 @@ impl RenderContext {
                     color: true,
                     depth: true,
                     depth_test: true,
-                    id: None,
+                    id: Some(object.id),
                     lighting: Some(Lighting::DefFull),
                 };
                 self.graphics.ru_set(prg, &runit);
 
                 prg.uniform_model(&object.trans, false);
                 object.mesh.bind();
-                bind_emissive_static(prg, &surface, 0.0);
+                bind_emissive_static(prg, &surface, self.params.emission_time);
                 object.mesh.draw();
+                self.record_motion(&object);
 
                 if object.shadow {
                     self.get_mut_chamber_ctx(object.chamber_index)?
@@ -141,12 +155,109 @@ impl RenderContext {
                         color: nalgebra::convert::<IVec3, Vec3>(emission) / 255.0
                             * fere_common::light::intensity_to_weight(*emission_intensity)
                             * point_light,
+                        attenuation: Default::default(),
+                        chamber_index: object.chamber_index,
+                    })))
+                } else {
+                    Ok(None)
+                }
+            }
+            RenderOp::DrawEmissiveDynamic(DrawEmissiveDynamic {
+                object,
+                general,
+                materials,
+                surface,
+                point_light,
+            }) => {
+                self.get_chamber_ctx(object.chamber_index)?;
+
+                // The average color of the current emission, for the point light.
+                // It's `None` for a textured one.
+                let emission = match surface.current {
+                    surface::CurrentEmission::Arbitrary(x) => {
+                        Some((nalgebra::convert::<IVec3, Vec3>(x.xyz()) / 255.0, x.w as u8))
+                    }
+                    surface::CurrentEmission::Material(x) => match materials.get(x) {
+                        Some(surface::EmissiveMaterial::Plain(
+                            surface::TexVar::U(e),
+                            surface::TexVar::U(em),
+                        )) => Some((nalgebra::convert::<IVec3, Vec3>(*e) / 255.0, *em)),
+                        Some(surface::EmissiveMaterial::Plain(_, _)) => None,
+                        Some(surface::EmissiveMaterial::Video(video, em)) => {
+                            video
+                                .update(surface.time)
+                                .map_err(|e| OpError::Other(e.to_string()))?;
+                            Some((video.average_color(), *em))
+                        }
+                        Some(surface::EmissiveMaterial::AnimatedUniform(keyframes)) => {
+                            let (e, em) = surface::animated_uniform_at(keyframes, surface.time);
+                            Some((e, (em * 255.0) as u8))
+                        }
+                        None => {
+                            return Err(OpError::Other(
+                                "`CurrentEmission::Material` out of `materials`".to_owned(),
+                            ))
+                        }
+                    },
+                };
+
+                let prg = self.graphics.prgs.standard.as_ref();
+                prg.bind();
+                let runit = RenderUnit {
+                    color: true,
+                    depth: true,
+                    depth_test: true,
+                    id: Some(object.id),
+                    lighting: Some(Lighting::DefFull),
+                };
+                self.graphics.ru_set(prg, &runit);
+
+                prg.uniform_model(&object.trans, false);
+                object.mesh.bind();
+                bind_general(prg, &general);
+                bind_emissive_dynamic(prg, &surface, &materials);
+                object.mesh.draw();
+                self.record_motion(&object);
+
+                if object.shadow {
+                    self.get_mut_chamber_ctx(object.chamber_index)?
+                        .shadow_objects
+                        .push(ChamberShadowObject {
+                            mesh: Arc::clone(&object.mesh),
+                            trans: object.trans,
+                        })
+                }
+
+                if let Some(point_light) = point_light {
+                    let (emission, emission_intensity) = match emission {
+                        None => return Err(OpError::Other("`point_light` in `DrawEmissiveDynamic` is not implemented for a textured surface.".to_owned())),
+                        Some(x) => x,
+                    };
+                    Ok(Some(RenderOp::AddPointLight(AddPointLight {
+                        pos: object.mesh.description().mean_pos,
+                        color: emission
+                            * fere_common::light::intensity_to_weight(emission_intensity)
+                            * point_light,
+                        attenuation: Default::default(),
                         chamber_index: object.chamber_index,
                     })))
                 } else {
                     Ok(None)
                 }
             }
+            RenderOp::DrawTransparent(x) => {
+                self.get_chamber_ctx(x.object.chamber_index)?;
+                if x.object.shadow {
+                    self.get_mut_chamber_ctx(x.object.chamber_index)?
+                        .shadow_objects
+                        .push(ChamberShadowObject {
+                            mesh: Arc::clone(&x.object.mesh),
+                            trans: x.object.trans,
+                        })
+                }
+                self.draw_transparents.push(x);
+                Ok(None)
+            }
             RenderOp::DrawWireFrame(DrawWireFrame {
                 mesh,
                 trans,
@@ -171,7 +282,7 @@ impl RenderContext {
                 Ok(None)
             }
             RenderOp::AddMajorLight(x) => {
-                self.get_chamber_ctx(x.chamber_index)?;
+                self.check_major_light_budget(x.chamber_index)?;
 
                 let result =
                     if self.params.debug_lightvolume_outline {
@@ -190,18 +301,51 @@ impl RenderContext {
                     .push(x);
                 Ok(result)
             }
+            RenderOp::MajorLightOmni(x) => {
+                self.check_major_light_budget(x.chamber_index)?;
+
+                let result = if self.params.debug_lightvolume_outline {
+                    Some(RenderOp::DrawWireFrame(DrawWireFrame {
+                        mesh: Arc::clone(&self.graphics.meshes().sphere),
+                        trans: glm::scale(
+                            &glm::translate(&Mat4::identity(), &x.pos),
+                            &Vec3::from_element(common::light_radius(&x.color, &x.attenuation)),
+                        ),
+                        color: IVec4::new(0, 255, 255, 255),
+                        width: 1.0,
+                    }))
+                } else {
+                    None
+                };
+                self.get_mut_chamber_ctx(x.chamber_index)?
+                    .omni_lights
+                    .push(x);
+                Ok(result)
+            }
             RenderOp::AddAmbientLight(x) => {
                 self.get_mut_chamber_ctx(x.chamber_index)?
                     .ambient_lights
                     .push(x);
                 Ok(None)
             }
+            RenderOp::AddEnvironmentLight(x) => {
+                self.get_mut_chamber_ctx(x.chamber_index)?
+                    .environment_lights
+                    .push(x);
+                Ok(None)
+            }
             RenderOp::AddPointLight(x) => {
                 self.get_mut_chamber_ctx(x.chamber_index)?
                     .point_lights
                     .push(x);
                 Ok(None)
             }
+            RenderOp::AddSunLight(x) => {
+                self.get_mut_chamber_ctx(x.chamber_index)?
+                    .sun_lights
+                    .push(x);
+                Ok(None)
+            }
             RenderOp::ShadeWithIv(x) => {
                 let chamber_index = x.chamber_index;
                 if self
@@ -253,6 +397,22 @@ impl RenderContext {
                 self.draw_billboarsd.push(x);
                 Ok(None)
             }
+            RenderOp::DrawMutableImage(x) => {
+                x.texture.upload();
+                self.draw_images.push(DrawImage {
+                    texture: x.texture.texture(),
+                    pos: x.pos,
+                    size: x.size,
+                    rotation: x.rotation,
+                    blend_mode: x.blend_mode,
+                    color: x.color,
+                });
+                Ok(None)
+            }
+            RenderOp::UpdateMutableTexture(x) => {
+                x.texture.upload();
+                Ok(None)
+            }
             RenderOp::ShowInternalTexture(x) => {
                 self.show_internal_textures.push(x);
                 Ok(None)
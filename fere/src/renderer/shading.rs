@@ -1,11 +1,12 @@
 use super::*;
+use crate::graphics::graphics::MAJOR_LIGHT_BATCH;
 
 impl RenderContext {
-    fn render_shadow_world(&self, chamber_index: ChamberIndex) {
+    /// `prg` must be bound already.
+    fn render_shadow_world(&self, chamber_index: ChamberIndex, prg: &Shader) {
         let chamber = self.chamber_contexts[chamber_index as usize]
             .as_ref()
             .unwrap();
-        let prg = self.graphics.prgs.shadow_1.bind();
         let runit = RenderUnit {
             color: false,
             depth: true,
@@ -32,6 +33,20 @@ impl RenderContext {
             .as_ref()
             .expect("You must set camera first");
 
+        // # Shadow phase
+        // Every major light has its own shadow map, so that they are shaded all together.
+        let major_lights: Vec<(LightDir, SetCamera)> = chamber
+            .major_lights
+            .iter()
+            .map(common::prepare_major_light)
+            .collect();
+        for (i, (_, light_camera)) in major_lights.iter().enumerate() {
+            self.graphics.bind_shadow(i);
+            let prg = self.graphics.prgs.shadow_1.bind();
+            prg.uniform_transformations(light_camera.projection_get(), light_camera.view_get());
+            self.render_shadow_world(chamber_index, prg);
+        }
+
         // # Direct lighting phase
         self.graphics.bind_deferred_pass2(false);
 
@@ -43,6 +58,7 @@ impl RenderContext {
             .collect();
         let prg = self.graphics.prgs.dr_2_omni.bind();
         self.graphics.bind_gbuffer(prg, 0);
+        self.graphics.bind_ambient_occlusion(prg, 10);
         for omni_light in omni_lights {
             prg.uniform_ambient(&omni_light.color);
             self.graphics.draw_lightvolume_ambient(
@@ -61,6 +77,7 @@ impl RenderContext {
             .collect();
         let prg = self.graphics.prgs.dr_2_ambient.bind();
         self.graphics.bind_gbuffer(prg, 0);
+        self.graphics.bind_ambient_occlusion(prg, 10);
         for omni_light in omni_lights {
             prg.uniform_ambient(&omni_light.color);
             self.graphics.draw_lightvolume_ambient(
@@ -71,35 +88,117 @@ impl RenderContext {
             );
         }
 
-        // - Major lights
-        for major_light in &chamber.major_lights {
-            // Step1: Draw the world again
-            let (light, camera) = common::prepare_major_light(major_light);
+        // - Environment from the sky
+        if let Some(skybox) = chamber.skybox.as_ref().or_else(|| self.skybox.as_ref()) {
+            if skybox.ambient > 0.0 {
+                let prg = self.graphics.prgs.dr_2_env.bind();
+                self.graphics.bind_gbuffer(prg, 0);
+                self.graphics.bind_ambient_occlusion(prg, 10);
+                self.graphics.bind_cube_texture(prg, 8, &skybox.texture);
+                prg.uniform_sky(skybox.ambient, (skybox.texture.max_lod() - 1.0).max(0.0));
+                self.graphics.draw_lightvolume_ambient(
+                    prg,
+                    &(chamber.chamber.config.bpos - Vec3::new(0.5, 0.5, 0.5)),
+                    &camera.pos,
+                    &(chamber.chamber.config.size + Vec3::new(1.0, 1.0, 1.0)),
+                );
+            }
+        }
 
-            self.graphics.bind_shadow(0);
-            let prg = self.graphics.prgs.shadow_1.bind();
-            prg.uniform_transformations(camera.projection_get(), camera.view_get());
-            self.render_shadow_world(chamber_index);
+        // - Major lights, shaded together as many as their shadow maps can be bound at once
+        let prg = self.graphics.prgs.dr_2.bind();
+        self.graphics.bind_gbuffer(prg, 0);
+        for (batch, lights) in major_lights.chunks(MAJOR_LIGHT_BATCH).enumerate() {
+            for (i, (light, _)) in lights.iter().enumerate() {
+                prg.uniform_light_dir(light, i);
+            }
+            prg.uniform_light_num(lights.len(), lights.len());
+            self.graphics
+                .bind_shadow_maps(prg, batch * MAJOR_LIGHT_BATCH, lights.len());
+            self.graphics.draw_lightvolume_ambient(
+                prg,
+                &(chamber.chamber.config.bpos - Vec3::new(0.5, 0.5, 0.5)),
+                &camera.pos,
+                &(chamber.chamber.config.size + Vec3::new(1.0, 1.0, 1.0)),
+            );
+        }
+
+        // - Volumetric fog, while the shadow maps of the major lights are alive
+        self.scatter_fog(chamber, &major_lights);
+
+        // - Omni-directional major lights
+        for omni_light in &chamber.omni_lights {
+            // Step1: Draw the world into each face of the cube
+            let (light, cameras) = common::prepare_omni_light(omni_light);
+
+            for (face, camera) in cameras.iter().enumerate() {
+                self.graphics.bind_shadow_cube(face);
+                let prg = self.graphics.prgs.shadow_cube.bind();
+                prg.uniform_transformations(camera.projection_get(), camera.view_get());
+                prg.uniform_camera(&omni_light.pos);
+                prg.uniform_far(common::omni_light_far(&light));
+                self.render_shadow_world(chamber_index, prg);
+            }
 
             self.graphics.bind_deferred_pass2(false);
-            let prg = self.graphics.prgs.dr_2.bind();
-            self.graphics.bind_shadow_map(prg, 0);
-            self.graphics.draw_lightvolume_dir(prg, &light, camera.pos);
+            let prg = self.graphics.prgs.dr_2_cube.bind();
+            self.graphics.bind_gbuffer(prg, 0);
+            self.graphics.bind_shadow_map_cube(prg);
+            prg.uniform_far(common::omni_light_far(&light));
+            self.graphics
+                .draw_lightvolume_uni(prg, &light, camera.pos, false);
+        }
+
+        // - Sun lights
+        let cascade_num = self.graphics.sun_cascade_num();
+        let resolution = self.graphics.sun_shadow_resolution();
+        let look = normalize(&(camera.look - camera.pos));
+        for sun_light in &chamber.sun_lights {
+            let cascades = common::prepare_sun_light(
+                sun_light,
+                camera,
+                &chamber.chamber.config.size,
+                cascade_num,
+                resolution,
+            );
+            for (i, cascade) in cascades.iter().enumerate() {
+                self.graphics.bind_shadow_sun(i);
+                let prg = self.graphics.prgs.shadow_1.bind();
+                prg.uniform_transformations(&cascade.light.trans, &Mat4::identity());
+                self.render_shadow_world(chamber_index, prg);
+
+                self.graphics.bind_deferred_pass2(false);
+                let prg = self.graphics.prgs.dr_2.bind();
+                self.graphics.bind_shadow_map_sun(prg, i);
+                prg.uniform_light_dir(&cascade.light, 0);
+                prg.uniform_light_num(1, 1);
+                prg.uniform_cascade(Some((&look, cascade.near, cascade.far)));
+                self.graphics.draw_lightvolume_ambient(
+                    prg,
+                    &(chamber.chamber.config.bpos - Vec3::new(0.5, 0.5, 0.5)),
+                    &camera.pos,
+                    &(chamber.chamber.config.size + Vec3::new(1.0, 1.0, 1.0)),
+                );
+                prg.uniform_cascade(None);
+            }
         }
 
         // Point lights
-        let prg = self.graphics.prgs.dr_2.bind();
-        for point_light in &chamber.point_lights {
-            let light = LightUni {
-                light: Light {
-                    pos: Vec4::new(point_light.pos.x, point_light.pos.y, point_light.pos.z, 1.0),
-                    color: point_light.color,
-                    shadow: true,
-                },
-                radius: 200.0,
-            };
-            self.graphics
-                .draw_lightvolume_uni(prg, &light, camera.pos, false);
+        match self.params.point_light_shading {
+            PointLightShading::LightVolume => {
+                let prg = self.graphics.prgs.dr_2.bind();
+                prg.uniform_light_num(1, 1);
+                for point_light in &chamber.point_lights {
+                    let light = common::prepare_point_light(point_light, true);
+                    self.graphics
+                        .draw_lightvolume_uni(prg, &light, camera.pos, false);
+                }
+            }
+            PointLightShading::Tiled => {
+                if !chamber.point_lights.is_empty() {
+                    self.shade_point_lights_tiled(chamber, camera);
+                }
+            }
         }
 
         // Irradiance volumes
@@ -109,6 +208,7 @@ impl RenderContext {
         ) {
             let prg = self.graphics.prgs.dr_2_irradiance.bind();
             self.graphics.bind_gbuffer(prg, 0);
+            self.graphics.bind_ambient_occlusion(prg, 10);
             self.graphics.bind_probe_volume(
                 prg,
                 8,
@@ -0,0 +1,18 @@
+use super::*;
+
+impl RenderContext {
+    pub(super) fn draw_skybox(&self) {
+        if let Some(skybox) = &self.skybox {
+            let camera = self
+                .camera_info
+                .as_ref()
+                .expect("You must set camera first");
+            self.graphics.draw_skybox(
+                &skybox.texture,
+                &(camera.projection_get() * camera.view_get()),
+                &camera.pos,
+                skybox.intensity,
+            );
+        }
+    }
+}
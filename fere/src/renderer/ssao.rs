@@ -0,0 +1,20 @@
+use super::*;
+
+impl RenderContext {
+    pub(super) fn ambient_occlusion(&self) {
+        let camera = self
+            .camera_info
+            .as_ref()
+            .expect("You must set camera first");
+        let params = if self.params.enable_ssao {
+            Some((self.params.ssao_strength, self.params.ssao_radius))
+        } else {
+            None
+        };
+        self.graphics.ambient_occlusion(
+            &(camera.projection_get() * camera.view_get()),
+            &camera.pos,
+            params,
+        );
+    }
+}
@@ -0,0 +1,24 @@
+use super::*;
+
+impl RenderContext {
+    pub(super) fn screen_space_reflections(&self) {
+        let camera = self
+            .camera_info
+            .as_ref()
+            .expect("You must set camera first");
+        let params = if self.params.enable_ssr {
+            Some((
+                self.params.ssr_max_steps,
+                self.params.ssr_max_distance,
+                self.params.ssr_thickness,
+            ))
+        } else {
+            None
+        };
+        self.graphics.screen_space_reflections(
+            &(camera.projection_get() * camera.view_get()),
+            &camera.pos,
+            params,
+        );
+    }
+}
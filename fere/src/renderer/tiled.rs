@@ -0,0 +1,110 @@
+use super::*;
+use crate::graphics::graphics::tiled::{TiledLights, LIGHT_TEXELS, TILE_SIZE};
+
+/// The range of tiles `[min, max]` that the light volume covers on the screen, or `None` if it's not visible.
+fn light_tile_range(
+    light: &LightUni,
+    camera: &SetCamera,
+    trans: &Mat4,
+    tiles: IVec2,
+) -> Option<(IVec2, IVec2)> {
+    let center = glm::vec4_to_vec3(&light.light.pos);
+    let radius = light.radius;
+    let whole = Some((IVec2::new(0, 0), tiles - IVec2::new(1, 1)));
+
+    let look = normalize(&(camera.look - camera.pos));
+    if dot(&(center - camera.pos), &look) < -radius {
+        return None;
+    }
+    if length(&(center - camera.pos)) < radius + camera.near {
+        return whole;
+    }
+
+    let mut min = Vec2::new(std::f32::MAX, std::f32::MAX);
+    let mut max = Vec2::new(std::f32::MIN, std::f32::MIN);
+    for i in 0..8 {
+        let corner = center
+            + Vec3::new(
+                if i & 1 == 0 { -radius } else { radius },
+                if i & 2 == 0 { -radius } else { radius },
+                if i & 4 == 0 { -radius } else { radius },
+            );
+        let p = trans * Vec4::new(corner.x, corner.y, corner.z, 1.0);
+        // A corner behind the camera; give up on tightening the bounds.
+        if p.w <= 0.0 {
+            return whole;
+        }
+        let ndc = Vec2::new(p.x / p.w, p.y / p.w);
+        min = Vec2::new(min.x.min(ndc.x), min.y.min(ndc.y));
+        max = Vec2::new(max.x.max(ndc.x), max.y.max(ndc.y));
+    }
+    if max.x < -1.0 || max.y < -1.0 || min.x > 1.0 || min.y > 1.0 {
+        return None;
+    }
+
+    let to_tile = |ndc: f32, n: i32| {
+        let pixel = (ndc.max(-1.0).min(1.0) * 0.5 + 0.5) * (n * TILE_SIZE) as f32;
+        ((pixel as i32) / TILE_SIZE).max(0).min(n - 1)
+    };
+    Some((
+        IVec2::new(to_tile(min.x, tiles.x), to_tile(min.y, tiles.y)),
+        IVec2::new(to_tile(max.x, tiles.x), to_tile(max.y, tiles.y)),
+    ))
+}
+
+/// Bins the lights into the tiles, returning (grid, list) as `TiledLights::new()` takes.
+fn bin_lights(lights: &[LightUni], camera: &SetCamera, tiles: IVec2) -> (Vec<i32>, Vec<i32>) {
+    let trans = camera.projection_get() * camera.view_get();
+    let mut bins: Vec<Vec<i32>> = vec![Vec::new(); (tiles.x * tiles.y) as usize];
+    for (i, light) in lights.iter().enumerate() {
+        if let Some((min, max)) = light_tile_range(light, camera, &trans, tiles) {
+            for y in min.y..=max.y {
+                for x in min.x..=max.x {
+                    bins[(y * tiles.x + x) as usize].push(i as i32);
+                }
+            }
+        }
+    }
+
+    let mut grid = Vec::with_capacity(bins.len() * 2);
+    let mut list = Vec::new();
+    for bin in bins {
+        grid.push(list.len() as i32);
+        grid.push(bin.len() as i32);
+        list.extend(bin);
+    }
+    (grid, list)
+}
+
+impl RenderContext {
+    pub(super) fn shade_point_lights_tiled(&self, chamber: &ChamberContext, camera: &SetCamera) {
+        let lights: Vec<LightUni> = chamber
+            .point_lights
+            .iter()
+            .map(|x| common::prepare_point_light(x, false))
+            .collect();
+
+        let mut light_buf = Vec::with_capacity(lights.len() * LIGHT_TEXELS * 4);
+        for light in &lights {
+            let (pos, color, att) = (
+                &light.light.pos,
+                &light.light.color,
+                &light.light.attenuation,
+            );
+            light_buf.extend_from_slice(&[pos.x, pos.y, pos.z, light.radius]);
+            light_buf.extend_from_slice(&[color.x, color.y, color.z, 0.0]);
+            light_buf.extend_from_slice(&[att.x, att.y, att.z, 0.0]);
+        }
+
+        let screen = self.graphics.screen_size();
+        let tiles = IVec2::new(
+            (screen.x + TILE_SIZE - 1) / TILE_SIZE,
+            (screen.y + TILE_SIZE - 1) / TILE_SIZE,
+        );
+        let (grid, list) = bin_lights(&lights, camera, tiles);
+        let tiled = TiledLights::new(&light_buf, tiles, &grid, &list);
+
+        let prg = self.graphics.prgs.dr_2_tiled.bind();
+        self.graphics.draw_lights_tiled(prg, &tiled, camera.pos);
+    }
+}
@@ -0,0 +1,35 @@
+use super::*;
+use crate::graphics::graphics::ToneMapParams;
+
+impl RenderContext {
+    pub(super) fn tone_map(&mut self) {
+        let ToneMapping { operator, exposure } = self.params.tone_mapping;
+        let operator = match operator {
+            ToneMapOperator::Clamp => 0,
+            ToneMapOperator::Reinhard => 1,
+            ToneMapOperator::AcesFilmic => 2,
+        };
+        let params = match exposure {
+            Exposure::Manual(exposure) => ToneMapParams {
+                operator,
+                exposure,
+                auto_exposure: false,
+                exposure_range: Vec2::new(exposure, exposure),
+                adaptation_rate: 1.0,
+            },
+            Exposure::Auto {
+                key,
+                adaptation_rate,
+                min,
+                max,
+            } => ToneMapParams {
+                operator,
+                exposure: key,
+                auto_exposure: true,
+                exposure_range: Vec2::new(min, max),
+                adaptation_rate,
+            },
+        };
+        self.graphics.tone_map(&params);
+    }
+}
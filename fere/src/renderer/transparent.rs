@@ -0,0 +1,141 @@
+use super::*;
+use crate::graphics::glmanager::shader::UNIFORM_MAX;
+
+fn object_center(object: &Object) -> Vec3 {
+    let p = object.mesh.description().mean_pos;
+    (object.trans * Vec4::new(p.x, p.y, p.z, 1.0)).xyz()
+}
+
+/// A sun light without the shadow, whose `w` of the position tells the shader that it's directional.
+fn prepare_sun_light(sun_light: &AddSunLight) -> LightUni {
+    let dir = normalize(&sun_light.dir);
+    LightUni {
+        light: Light {
+            pos: Vec4::new(-dir.x, -dir.y, -dir.z, 0.0),
+            color: sun_light.color,
+            shadow: false,
+            attenuation: Vec3::new(1.0, 0.0, 0.0),
+        },
+        radius: 0.0,
+    }
+}
+
+impl RenderContext {
+    /// Renders all the transparent objects of the frame, from back to front.
+    ///
+    /// Call this after shading all chambers.
+    pub(super) fn render_transparents(&mut self) {
+        if self.draw_transparents.is_empty() {
+            return;
+        }
+        let cpos = self
+            .camera_info
+            .as_ref()
+            .expect("You must set camera first")
+            .pos;
+        let mut draws: Vec<(f32, DrawTransparent)> = std::mem::take(&mut self.draw_transparents)
+            .into_iter()
+            .map(|x| (length(&(object_center(&x.object) - cpos)), x))
+            .collect();
+        draws.sort_by(|x, y| y.0.partial_cmp(&x.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        self.graphics.bind_forward();
+        let prg = self.graphics.prgs.standard_forward.bind();
+        let runit = RenderUnit {
+            color: true,
+            depth: false,
+            depth_test: false,
+            id: None,
+            lighting: None,
+        };
+        self.graphics.ru_set(prg, &runit);
+        self.graphics.bind_gbuffer(prg, 8);
+        prg.uniform_camera(&cpos);
+
+        let mut too_many_lights = false;
+        for (_, DrawTransparent { object, surface }) in &draws {
+            let chamber = self.chamber_contexts[object.chamber_index as usize]
+                .as_ref()
+                .unwrap();
+            let center = object_center(object);
+
+            let mut count = 0;
+            for major_light in chamber.major_lights.iter().take(UNIFORM_MAX) {
+                let (light, _) = common::prepare_major_light(major_light);
+                prg.uniform_light_dir(&light, count);
+                count += 1;
+            }
+            let major_num = count;
+
+            let lights = chamber.sun_lights.iter().map(prepare_sun_light).chain(
+                chamber
+                    .omni_lights
+                    .iter()
+                    .map(|x| common::prepare_omni_light(x).0),
+            );
+            for light in lights.take(UNIFORM_MAX - count) {
+                prg.uniform_light_uni(&light, count);
+                count += 1;
+            }
+
+            // Take the nearest ones if there are too many
+            let mut point_lights: Vec<&AddPointLight> = chamber.point_lights.iter().collect();
+            point_lights.sort_by(|x, y| {
+                length(&(x.pos - center))
+                    .partial_cmp(&length(&(y.pos - center)))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+            for point_light in point_lights.iter().take(UNIFORM_MAX - count) {
+                let light = common::prepare_point_light(point_light, false);
+                prg.uniform_light_uni(&light, count);
+                count += 1;
+            }
+            if count
+                < chamber.major_lights.len()
+                    + chamber.sun_lights.len()
+                    + chamber.omni_lights.len()
+                    + chamber.point_lights.len()
+            {
+                too_many_lights = true;
+            }
+            prg.uniform_light_num(major_num, count);
+
+            let ambient: Vec3 = chamber.ambient_lights.iter().map(|x| x.color).sum();
+            prg.uniform_ambient(&ambient);
+
+            // The irradiance of every environment light adds up, but only the first one reflects.
+            let mut irradiance = vec![Vec3::zeros(); 18];
+            for light in &chamber.environment_lights {
+                for (x, y) in irradiance.iter_mut().zip(light.map.irradiance()) {
+                    *x += y * light.intensity;
+                }
+            }
+            prg.uniform_sh(&irradiance);
+            if let Some(light) = chamber.environment_lights.first() {
+                self.graphics.bind_environment_map(prg, 3, &light.map);
+                prg.uniform_ibl(light.intensity, light.map.levels());
+            } else {
+                prg.uniform_ibl(0.0, 0);
+            }
+            match chamber.skybox.as_ref().or_else(|| self.skybox.as_ref()) {
+                Some(skybox) if skybox.ambient > 0.0 => {
+                    self.graphics.bind_cube_texture(prg, 7, &skybox.texture);
+                    prg.uniform_sky(skybox.ambient, (skybox.texture.max_lod() - 1.0).max(0.0));
+                }
+                _ => prg.uniform_sky(0.0, 0.0),
+            }
+
+            prg.uniform_model(&object.trans, false);
+            object.mesh.bind();
+            bind_transparent(prg, surface);
+            object.mesh.draw();
+        }
+
+        if too_many_lights {
+            self.logs.push(FrameLog::new(format!(
+                "Transparent objects are lit by at most {} lights",
+                UNIFORM_MAX
+            )));
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use fere_common::*;
+use fere_resources::texture::TextureData;
+use gl::types::*;
+
+/// A cube map made of 6 square faces, for the skybox and the environment lighting.
+#[derive(Debug)]
+pub struct CubeTexture {
+    pub name: String,
+    // If it's not from the particular files, then None
+    pub path: Option<String>,
+    /// The size of each face.
+    pub size: IVec2,
+
+    // CPU things - will be purged from memory after buffer
+    data: Option<Vec<TextureData>>,
+
+    // GPU things - will exist only after buffer
+    tex: GLuint,
+}
+
+impl CubeTexture {
+    /// `faces` must be in the order of +X, -X, +Y, -Y, +Z, -Z, and be squares of the same size.
+    ///
+    /// It's named after the first face.
+    pub fn new(path: Option<String>, faces: Vec<TextureData>) -> Self {
+        assert_eq!(faces.len(), 6, "A cube map requires 6 faces");
+        let size = faces[0].size;
+        assert!(
+            size.x == size.y && faces.iter().all(|x| x.size == size),
+            "Faces of a cube map must be squares of the same size"
+        );
+        CubeTexture {
+            name: faces[0].name.clone(),
+            path,
+            size,
+            data: Some(faces),
+            tex: 0,
+        }
+    }
+
+    pub fn buffer(&mut self) {
+        assert!(
+            crate::is_main_thread(),
+            "This operation must be done in the main thread"
+        );
+        unsafe {
+            gl::GenTextures(1, &mut self.tex);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.tex);
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+
+            for (i, data) in self.data.take().unwrap().iter().enumerate() {
+                let format = match data.channel {
+                    1 => gl::RED,
+                    3 => gl::RGB,
+                    4 => gl::RGBA,
+                    _ => panic!("Invalid Image channel set"),
+                };
+                gl::TexImage2D(
+                    gl::TEXTURE_CUBE_MAP_POSITIVE_X + i as GLenum,
+                    0,
+                    format as i32,
+                    self.size.x,
+                    self.size.y,
+                    0,
+                    format,
+                    gl::UNSIGNED_BYTE,
+                    data.data.as_ptr().cast(),
+                );
+            }
+
+            // Blurry mipmaps are used for the diffuse environment lighting
+            gl::GenerateMipmap(gl::TEXTURE_CUBE_MAP);
+            gl::Enable(gl::TEXTURE_CUBE_MAP_SEAMLESS);
+            gl::TexParameteri(
+                gl::TEXTURE_CUBE_MAP,
+                gl::TEXTURE_MIN_FILTER,
+                gl::LINEAR_MIPMAP_LINEAR as i32,
+            );
+            gl::TexParameteri(
+                gl::TEXTURE_CUBE_MAP,
+                gl::TEXTURE_MAG_FILTER,
+                gl::LINEAR as i32,
+            );
+            for wrap in &[gl::TEXTURE_WRAP_S, gl::TEXTURE_WRAP_T, gl::TEXTURE_WRAP_R] {
+                gl::TexParameteri(gl::TEXTURE_CUBE_MAP, *wrap, gl::CLAMP_TO_EDGE as i32);
+            }
+        }
+    }
+
+    /// The level of the smallest mipmap.
+    pub fn max_lod(&self) -> f32 {
+        (self.size.x.max(1) as f32).log2().floor()
+    }
+
+    /// Reads back the RGB texels of each face at the given mipmap level, with its size.
+    pub(crate) fn read_faces(&self, level: usize) -> (Vec<Vec<f32>>, usize) {
+        debug_assert!(self.data.is_none(), "read_faces() on an unbufferd texure");
+        let size = (self.size.x as usize >> level).max(1);
+        let faces = (0..6)
+            .map(|i| {
+                let mut buf = vec![0.0_f32; size * size * 3];
+                unsafe {
+                    gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+                    gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.tex);
+                    gl::GetTexImage(
+                        gl::TEXTURE_CUBE_MAP_POSITIVE_X + i as GLenum,
+                        level as i32,
+                        gl::RGB,
+                        gl::FLOAT,
+                        buf.as_mut_ptr().cast(),
+                    );
+                }
+                buf
+            })
+            .collect();
+        (faces, size)
+    }
+
+    pub(crate) fn raw_get(&self) -> GLuint {
+        debug_assert!(self.data.is_none(), "raw_get() on an unbufferd texure");
+        self.tex
+    }
+
+    pub(crate) fn bind_at(&self, index: u32) {
+        debug_assert!(self.data.is_none(), "bind() on an unbufferd texure");
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + index);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.tex);
+        }
+    }
+}
+
+impl Drop for CubeTexture {
+    fn drop(&mut self) {
+        assert!(
+            crate::is_main_thread(),
+            "Texture must be dropped in the main thread"
+        );
+        if self.data.is_none() {
+            unsafe {
+                gl::DeleteTextures(1, &self.tex);
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::graphics::graphics::texture_internal::TextureInternalCube;
+use fere_common::*;
+
+/// Image-based lighting prepared from an environment cube map, with `Fere::create_environment_map()`.
+pub struct EnvironmentMap {
+    /// The specular reflection, prefiltered with the roughness increasing along the mipmap levels.
+    specular: TextureInternalCube,
+    levels: usize,
+
+    /// The diffuse irradiance in SH coefficients.
+    irradiance: Vec<Vec3>,
+}
+
+impl std::fmt::Debug for EnvironmentMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EnvironmentMap")
+            .field("levels", &self.levels)
+            .field("irradiance", &self.irradiance)
+            .finish()
+    }
+}
+
+impl Drop for EnvironmentMap {
+    fn drop(&mut self) {
+        assert!(
+            crate::is_main_thread(),
+            "EnvironmentMap must be dropped in the main thread"
+        );
+    }
+}
+
+impl EnvironmentMap {
+    pub(crate) fn new(specular: TextureInternalCube, levels: usize, irradiance: Vec<Vec3>) -> Self {
+        Self {
+            specular,
+            levels,
+            irradiance,
+        }
+    }
+
+    pub(crate) fn specular(&self) -> &TextureInternalCube {
+        &self.specular
+    }
+
+    /// The number of the mipmap levels of the specular, from the roughness 0 to 1.
+    pub fn levels(&self) -> usize {
+        self.levels
+    }
+
+    pub fn irradiance(&self) -> &[Vec3] {
+        &self.irradiance
+    }
+}
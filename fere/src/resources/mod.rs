@@ -1,6 +1,14 @@
+mod cube_texture;
+mod environment_map;
 mod mesh;
+mod mutable_texture;
 pub mod surface;
 mod texture;
+mod video;
 
+pub use cube_texture::CubeTexture;
+pub use environment_map::EnvironmentMap;
 pub use mesh::Mesh;
+pub use mutable_texture::MutableTexture;
 pub use texture::Texture;
+pub use video::Video;
@@ -0,0 +1,119 @@
+use super::Texture;
+use fere_common::*;
+use fere_resources::texture::TextureData;
+use std::sync::{Arc, Mutex};
+
+/// An RGBA texture whose pixels can be written from any thread.
+///
+/// The written pixels are kept in the CPU, and only the dirty region is uploaded
+/// on the render thread by `DrawMutableImage` or `UpdateMutableTexture`.
+/// Use `texture()` to put it on a surface.
+#[derive(Debug)]
+pub struct MutableTexture {
+    pub name: String,
+    pub size: IVec2,
+
+    texture: Arc<Texture>,
+    state: Mutex<MutableTextureState>,
+}
+
+#[derive(Debug)]
+struct MutableTextureState {
+    pixels: Vec<u8>,
+    /// (min, max) of the pixels written since the last upload, where max is exclusive.
+    dirty: Option<(IVec2, IVec2)>,
+}
+
+impl MutableTextureState {
+    fn mark_dirty(&mut self, min: IVec2, max: IVec2) {
+        self.dirty = Some(match self.dirty {
+            Some((min_, max_)) => (
+                IVec2::new(min.x.min(min_.x), min.y.min(min_.y)),
+                IVec2::new(max.x.max(max_.x), max.y.max(max_.y)),
+            ),
+            None => (min, max),
+        });
+    }
+}
+
+impl MutableTexture {
+    /// Creates a texture filled with `color`.
+    pub fn new(name: String, size: IVec2, color: IVec4) -> Self {
+        assert!(size.x > 0 && size.y > 0);
+        let pixel: Vec<u8> = color.iter().map(|x| *x as u8).collect();
+        let pixels = pixel.repeat((size.x * size.y) as usize);
+        let texture = Texture::new(
+            None,
+            TextureData {
+                name: name.clone(),
+                data: pixels.clone(),
+                size,
+                channel: 4,
+            },
+        );
+        MutableTexture {
+            name,
+            size,
+            texture: Arc::new(texture),
+            state: Mutex::new(MutableTextureState {
+                pixels,
+                dirty: None,
+            }),
+        }
+    }
+
+    /// It must be called before sharing the texture, as `Texture::buffer()`.
+    pub fn buffer(&mut self) {
+        Arc::get_mut(&mut self.texture)
+            .expect("buffer() on a shared mutable texture")
+            .buffer();
+    }
+
+    /// The texture to use in surfaces. It shows the pixels as of the last upload.
+    pub fn texture(&self) -> Arc<Texture> {
+        Arc::clone(&self.texture)
+    }
+
+    pub fn set_pixel(&self, pos: IVec2, color: IVec4) {
+        self.write_region(pos, IVec2::new(1, 1), |_| color);
+    }
+
+    pub fn fill(&self, color: IVec4) {
+        self.write_region(IVec2::new(0, 0), self.size, |_| color);
+    }
+
+    /// Writes the region of `size` at `pos`, with the color for each pixel given by `f`.
+    ///
+    /// The region is clipped by the texture.
+    pub fn write_region(&self, pos: IVec2, size: IVec2, f: impl Fn(IVec2) -> IVec4) {
+        let min = IVec2::new(pos.x.max(0), pos.y.max(0));
+        let max = IVec2::new(
+            (pos.x + size.x).min(self.size.x),
+            (pos.y + size.y).min(self.size.y),
+        );
+        if min.x >= max.x || min.y >= max.y {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        for y in min.y..max.y {
+            for x in min.x..max.x {
+                let pixel = IVec2::new(x, y);
+                let color = f(pixel);
+                let i = ((y * self.size.x + x) * 4) as usize;
+                for c in 0..4 {
+                    state.pixels[i + c] = color[c].max(0).min(255) as u8;
+                }
+            }
+        }
+        state.mark_dirty(min, max);
+    }
+
+    /// Uploads the dirty region, if any.
+    pub(crate) fn upload(&self) {
+        let mut state = self.state.lock().unwrap();
+        if let Some((min, max)) = state.dirty.take() {
+            self.texture
+                .write_region_rgba(min, max - min, self.size.x, &state.pixels);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Mesh, Texture};
+use super::{Mesh, Texture, Video};
 use fere_common::*;
 use std::{fmt::Debug, sync::Arc};
 
@@ -43,7 +43,7 @@ pub struct TypePackSource;
 impl TypePack for TypePackSource {
     type TexturePointer1 = TexVar<Arc<Texture>, u8>;
     type TexturePointer3 = TexVar<Arc<Texture>, IVec3>;
-    type VideoPointer = Arc<u8>;
+    type VideoPointer = Arc<Video>;
     type MeshPointer = Arc<Mesh>;
     type NamePointer = Arc<String>;
 }
@@ -53,7 +53,7 @@ pub struct TypePackInstance;
 impl TypePack for TypePackInstance {
     type TexturePointer1 = TexVar<Arc<Texture>, u8>;
     type TexturePointer3 = TexVar<Arc<Texture>, IVec3>;
-    type VideoPointer = Arc<u8>;
+    type VideoPointer = Arc<Video>;
     type MeshPointer = Arc<Mesh>;
     type NamePointer = Arc<String>;
 }
@@ -99,6 +99,7 @@ impl Clone for GeneralI {
 #[derive(Debug)]
 pub struct Transparent<T: TypePack> {
     pub general: General<T>,
+    /// The transparency of the surface, where 0 is opaque.
     pub alpha: T::TexturePointer1,
 }
 pub type TransparentS = Transparent<TypePackSource>;
@@ -126,6 +127,8 @@ pub struct Timepoint<T: TypePack> {
     pub emission: T::TexturePointer3,
     pub emission_intensity: T::TexturePointer1,
     /// smoothness to next timepoint: 0~1
+    ///
+    /// 0 jumps to the next one at the end, and 1 eases into it over the whole interval.
     pub smoothness: f32,
 }
 pub type TimepointS = Timepoint<TypePackSource>;
@@ -207,9 +210,7 @@ impl EmissiveMaterialS {
             EmissiveMaterial::Plain(e, em) => {
                 EmissiveMaterial::Plain(e.to_instance(), em.to_instance())
             }
-            EmissiveMaterial::Video(_v, _em) => {
-                unimplemented!()
-            }
+            EmissiveMaterial::Video(v, em) => EmissiveMaterial::Video(Arc::clone(v), *em),
             EmissiveMaterial::AnimatedUniform(x) => EmissiveMaterial::AnimatedUniform(x.clone()),
         }
     }
@@ -224,6 +225,39 @@ impl Clone for EmissiveMaterialI {
     }
 }
 
+/// Plays the keyframes of `EmissiveMaterial::AnimatedUniform` at `time`, looping over them.
+///
+/// It returns (color, intensity) in [0, 1], which are linearly interpolated
+/// from each keyframe to the next one over its duration.
+pub fn animated_uniform_at(keyframes: &[(IVec3, u8, u32)], time: u32) -> (Vec3, f32) {
+    let total: u32 = keyframes.iter().map(|(_, _, duration)| duration).sum();
+    if keyframes.is_empty() {
+        return (Vec3::zeros(), 0.0);
+    } else if total == 0 {
+        let (color, intensity, _) = keyframes[0];
+        return (
+            nalgebra::convert::<IVec3, Vec3>(color) / 255.0,
+            intensity as f32 / 255.0,
+        );
+    }
+
+    let mut time = time % total;
+    for (i, (color, intensity, duration)) in keyframes.iter().enumerate() {
+        if time < *duration {
+            let (color2, intensity2, _) = keyframes[(i + 1) % keyframes.len()];
+            let t = time as f32 / *duration as f32;
+            let color: Vec3 = nalgebra::convert(*color);
+            let color2: Vec3 = nalgebra::convert(color2);
+            return (
+                (color * (1.0 - t) + color2 * t) / 255.0,
+                (*intensity as f32 * (1.0 - t) + intensity2 as f32 * t) / 255.0,
+            );
+        }
+        time -= duration;
+    }
+    unreachable!()
+}
+
 #[derive(Clone, Debug)]
 pub enum CurrentEmission {
     Material(usize),
@@ -233,6 +267,7 @@ pub enum CurrentEmission {
 /// This material is special; it is same for both source and instance!
 pub struct EmissiveDynamic {
     pub current: CurrentEmission,
+    /// The time in game frame, used to play `Video` and `AnimatedUniform`.
     pub time: u32,
 }
 
@@ -243,3 +278,58 @@ pub enum SurfaceKind {
     EmissiveStatic,
     EmissiveDynamic,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_emission(actual: (Vec3, f32), color: Vec3, intensity: f32) {
+        assert!((actual.0 - color).norm() < 0.0001, "{:?}", actual);
+        assert!((actual.1 - intensity).abs() < 0.0001, "{:?}", actual);
+    }
+
+    #[test]
+    fn animated_uniform_interpolates_and_loops() {
+        let keyframes = [
+            (IVec3::new(255, 0, 0), 255, 10),
+            (IVec3::new(0, 255, 0), 0, 10),
+        ];
+        let red = Vec3::new(1.0, 0.0, 0.0);
+        let half = Vec3::new(0.5, 0.5, 0.0);
+
+        assert_emission(animated_uniform_at(&keyframes, 0), red, 1.0);
+        assert_emission(animated_uniform_at(&keyframes, 5), half, 0.5);
+        // The last one blends back to the first one
+        assert_emission(animated_uniform_at(&keyframes, 15), half, 0.5);
+        assert_emission(animated_uniform_at(&keyframes, 20), red, 1.0);
+        assert_emission(animated_uniform_at(&keyframes, 45), half, 0.5);
+    }
+
+    #[test]
+    fn animated_uniform_zero_duration() {
+        // A keyframe without duration is only the target of the previous one
+        let keyframes = [
+            (IVec3::new(255, 0, 0), 255, 10),
+            (IVec3::new(0, 0, 255), 255, 0),
+            (IVec3::new(0, 255, 0), 0, 10),
+        ];
+        assert_emission(
+            animated_uniform_at(&keyframes, 5),
+            Vec3::new(0.5, 0.0, 0.5),
+            1.0,
+        );
+        assert_emission(
+            animated_uniform_at(&keyframes, 10),
+            Vec3::new(0.0, 1.0, 0.0),
+            0.0,
+        );
+
+        let still = [(IVec3::new(0, 0, 255), 51, 0)];
+        assert_emission(
+            animated_uniform_at(&still, 7),
+            Vec3::new(0.0, 0.0, 1.0),
+            0.2,
+        );
+        assert_emission(animated_uniform_at(&[], 7), Vec3::zeros(), 0.0);
+    }
+}
@@ -85,6 +85,46 @@ impl Texture {
         unimplemented!()
     }
 
+    /// Uploads an RGBA region of `size` at `offset` in pixels.
+    ///
+    /// `data` is the whole image with `row_length` pixels for each row,
+    /// and the region is read from `offset` of it.
+    pub(crate) fn write_region_rgba(
+        &self,
+        offset: IVec2,
+        size: IVec2,
+        row_length: i32,
+        data: &[u8],
+    ) {
+        assert!(
+            crate::is_main_thread(),
+            "This operation must be done in the main thread"
+        );
+        assert!(offset.x >= 0 && offset.y >= 0);
+        assert!(offset.x + size.x <= self.size.x && offset.y + size.y <= self.size.y);
+        unsafe {
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::PixelStorei(gl::UNPACK_ROW_LENGTH, row_length);
+            gl::PixelStorei(gl::UNPACK_SKIP_PIXELS, offset.x);
+            gl::PixelStorei(gl::UNPACK_SKIP_ROWS, offset.y);
+            gl::BindTexture(gl::TEXTURE_2D, self.tex);
+            gl::TexSubImage2D(
+                gl::TEXTURE_2D,
+                0,
+                offset.x,
+                offset.y,
+                size.x,
+                size.y,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                data.as_ptr().cast(),
+            );
+            gl::PixelStorei(gl::UNPACK_ROW_LENGTH, 0);
+            gl::PixelStorei(gl::UNPACK_SKIP_PIXELS, 0);
+            gl::PixelStorei(gl::UNPACK_SKIP_ROWS, 0);
+        }
+    }
+
     pub fn write_buffer(&self, data: &[u8]) {
         assert!(
             crate::is_main_thread(),
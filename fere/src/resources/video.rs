@@ -0,0 +1,192 @@
+use super::Texture;
+use crate::Error;
+use fere_common::*;
+use fere_resources::texture::TextureData;
+use std::fs::File;
+use std::io::BufReader;
+use std::sync::Mutex;
+
+type Decoder = y4m::Decoder<BufReader<File>>;
+
+/// A Y4M video which streams its frames into a texture, for the emissive materials.
+///
+/// It's decoded sequentially from the file as the frames are requested,
+/// and rewinds when it's asked for an earlier frame or reaches the end.
+#[derive(Debug)]
+pub struct Video {
+    pub name: String,
+    pub path: String,
+    pub size: IVec2,
+
+    texture: Texture,
+    state: Mutex<VideoState>,
+}
+
+struct VideoState {
+    decoder: Decoder,
+    /// The index of the frame which will be decoded next.
+    next: u32,
+    /// The frame in the texture.
+    shown: Option<u32>,
+    /// Known after the first time it reaches the end.
+    frame_count: Option<u32>,
+    average: Vec3,
+    rgb: Vec<u8>,
+}
+
+impl std::fmt::Debug for VideoState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VideoState")
+            .field("next", &self.next)
+            .field("shown", &self.shown)
+            .field("frame_count", &self.frame_count)
+            .finish()
+    }
+}
+
+fn open(path: &str) -> Result<Decoder, Error> {
+    let file = File::open(path).map_err(|e| Error::VideoError(format!("{}: {}", path, e)))?;
+    let decoder = y4m::decode(BufReader::new(file))
+        .map_err(|e| Error::VideoError(format!("{}: {:?}", path, e)))?;
+    if decoder.get_bytes_per_sample() != 1 {
+        return Err(Error::VideoError(format!(
+            "{}: Only 8-bit videos are supported",
+            path
+        )));
+    }
+    Ok(decoder)
+}
+
+/// (horizontal, vertical) subsampling of the chroma planes, or `None` if there's no chroma.
+fn chroma_subsampling(colorspace: y4m::Colorspace) -> Result<Option<(usize, usize)>, String> {
+    match colorspace {
+        y4m::Colorspace::Cmono => Ok(None),
+        y4m::Colorspace::C420
+        | y4m::Colorspace::C420jpeg
+        | y4m::Colorspace::C420paldv
+        | y4m::Colorspace::C420mpeg2 => Ok(Some((2, 2))),
+        y4m::Colorspace::C422 => Ok(Some((2, 1))),
+        y4m::Colorspace::C444 => Ok(Some((1, 1))),
+        x => Err(format!("Unsupported colorspace {:?}", x)),
+    }
+}
+
+impl Video {
+    /// Opens the video, and reads its header.
+    pub fn new(path: String) -> Result<Self, Error> {
+        let decoder = open(&path)?;
+        chroma_subsampling(decoder.get_colorspace())
+            .map_err(|e| Error::VideoError(format!("{}: {}", path, e)))?;
+        let size = IVec2::new(decoder.get_width() as i32, decoder.get_height() as i32);
+        let name = std::path::Path::new(&path)
+            .file_stem()
+            .map_or_else(|| path.clone(), |x| x.to_string_lossy().into_owned());
+        let pixels = (size.x * size.y) as usize;
+        let texture = Texture::new(
+            Some(path.clone()),
+            TextureData {
+                name: name.clone(),
+                data: vec![0; pixels * 3],
+                size,
+                channel: 3,
+            },
+        );
+        Ok(Video {
+            name,
+            path,
+            size,
+            texture,
+            state: Mutex::new(VideoState {
+                decoder,
+                next: 0,
+                shown: None,
+                frame_count: None,
+                average: Vec3::zeros(),
+                rgb: vec![0; pixels * 3],
+            }),
+        })
+    }
+
+    pub fn buffer(&mut self) {
+        self.texture.buffer();
+    }
+
+    /// Uploads the frame of `time` to the texture, where a frame is shown for each step of `time`.
+    pub(crate) fn update(&self, time: u32) -> Result<(), Error> {
+        assert!(
+            crate::is_main_thread(),
+            "This operation must be done in the main thread"
+        );
+        let mut guard = self.state.lock().unwrap();
+        let state = &mut *guard;
+        let target = state.frame_count.map_or(time, |n| time % n);
+        if state.shown == Some(target) {
+            return Ok(());
+        }
+        if target < state.next {
+            state.decoder = open(&self.path)?;
+            state.next = 0;
+        }
+
+        let subsampling = chroma_subsampling(state.decoder.get_colorspace()).unwrap();
+        let width = self.size.x as usize;
+        loop {
+            match state.decoder.read_frame() {
+                Ok(frame) => {
+                    if state.next < target {
+                        state.next += 1;
+                        continue;
+                    }
+                    let (y, u, v) = (
+                        frame.get_y_plane(),
+                        frame.get_u_plane(),
+                        frame.get_v_plane(),
+                    );
+                    let mut sum = Vec3::zeros();
+                    for (i, luma) in y.iter().enumerate() {
+                        let (cb, cr) = if let Some((sx, sy)) = subsampling {
+                            let j = (i / width / sy) * ((width + sx - 1) / sx) + (i % width) / sx;
+                            (u[j] as f32 - 128.0, v[j] as f32 - 128.0)
+                        } else {
+                            (0.0, 0.0)
+                        };
+                        // BT.601
+                        let luma = (*luma as f32 - 16.0) * 1.164;
+                        let rgb = [
+                            luma + 1.596 * cr,
+                            luma - 0.392 * cb - 0.813 * cr,
+                            luma + 2.017 * cb,
+                        ];
+                        for (c, x) in rgb.iter().enumerate() {
+                            let x = x.max(0.0).min(255.0);
+                            state.rgb[i * 3 + c] = x as u8;
+                            sum[c] += x;
+                        }
+                    }
+                    state.average = sum / (y.len().max(1) as f32 * 255.0);
+                    state.next += 1;
+                    state.shown = Some(target);
+                    self.texture.write_buffer(&state.rgb);
+                    return Ok(());
+                }
+                Err(y4m::Error::EOF) if state.next > 0 => break,
+                Err(e) => return Err(Error::VideoError(format!("{}: {:?}", self.path, e))),
+            }
+        }
+
+        // Learn the length, and start over
+        let frame_count = state.next;
+        state.frame_count = Some(frame_count);
+        drop(guard);
+        self.update(time % frame_count)
+    }
+
+    /// The average color of the frame in the texture, in [0, 1].
+    pub fn average_color(&self) -> Vec3 {
+        self.state.lock().unwrap().average
+    }
+
+    pub(crate) fn bind_at(&self, index: u32) {
+        self.texture.bind_at(index)
+    }
+}
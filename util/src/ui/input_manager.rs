@@ -3,13 +3,6 @@ use std::collections::HashSet;
 
 pub type KeyboardKey = usize;
 
-#[derive(Clone, Debug)]
-pub struct FbLookUp {
-    pub id: u64,
-    pub pos: Vec3,
-    pub norm: Vec3,
-}
-
 #[derive(Clone, Debug)]
 pub struct MouseSignal {
     pub left: bool,
@@ -45,8 +38,10 @@ impl MouseSignal {
 #[derive(Clone, Debug)]
 /// Represents all information about user input for a single frame
 pub struct InputImage {
-    //pub fbl_cursor: FbLookUp,
-    //pub fbl_look: FbLookUp,
+    /// What's under the mouse cursor
+    pub fbl_cursor: Option<FbLookUp>,
+    /// What's at the center of the screen
+    pub fbl_look: Option<FbLookUp>,
     pub mouse_pos: IVec2,
     pub mouse_pos_delta: IVec2,
 
@@ -84,7 +79,9 @@ impl InputManager {
         self.image.as_ref().unwrap().clone()
     }
 
-    pub fn update<'a>(&mut self, imgui_ctx: &imgui::Ui<'a>) {
+    /// `fere` must be given between `end_frame()` and the next `new_frame()`
+    /// to look up the last rendered frame.
+    pub fn update<'a>(&mut self, imgui_ctx: &imgui::Ui<'a>, fere: &Fere) {
         let io_source = imgui_ctx.io();
         // TODO : This MAX check is platform dependent?
         self.last_valid_mouse_pos = if io_source.mouse_pos == [-f32::MAX, -f32::MAX] {
@@ -142,7 +139,16 @@ impl InputManager {
             }
         }
 
+        let fbl_cursor = if mouse_on_screen {
+            fere.look_up(mouse_pos)
+        } else {
+            None
+        };
+        let fbl_look = fere.look_up(self.screen_size / 2);
+
         self.image.replace(InputImage {
+            fbl_cursor,
+            fbl_look,
             mouse_pos,
             mouse_pos_delta,
             mouse_signal,
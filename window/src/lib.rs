@@ -34,6 +34,31 @@ pub trait ProgramWithImgui: Send + Sync + 'static {
     fn update(&mut self, imgui_ctx: ImgUiContext) -> String;
 }
 
+/// An OpenGL context without a visible window, for offline jobs like baking.
+///
+/// It's made current on creation. Keep it alive while using Fere.
+/// Note that some platforms (e.g. X11) still need a display connection for it.
+pub struct HeadlessContext {
+    _context: glutin::Context<glutin::PossiblyCurrent>,
+    _event_loop: EventLoop<()>,
+}
+
+pub fn create_headless_context(size: IVec2) -> HeadlessContext {
+    let event_loop = EventLoop::new();
+    let context = glutin::ContextBuilder::new()
+        .build_headless(
+            &event_loop,
+            glutin::dpi::PhysicalSize::new(size.x as u32, size.y as u32),
+        )
+        .unwrap();
+    let context = unsafe { context.make_current().unwrap() };
+    gl::load_with(|symbol| context.get_proc_address(symbol));
+    HeadlessContext {
+        _context: context,
+        _event_loop: event_loop,
+    }
+}
+
 pub fn run<T: Program>(config: WindowConfig) {
     let event_loop = EventLoop::new();
     let _context = glutin::ContextBuilder::new().with_vsync(false);